@@ -0,0 +1,110 @@
+use crate::error::RustlingsError;
+use crate::exercise::{Exercise, ExerciseOutput, Mode, COMPILER_FLAGS};
+use crate::progress::hash_bytes;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+const CACHE_DIR: &str = ".rustlings/cache";
+
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+// Stop using the cache for the rest of the process, for `--no-cache`
+pub fn disable() {
+    DISABLED.store(true, Ordering::SeqCst);
+}
+
+// Remove every cached result
+pub fn clear() -> io::Result<()> {
+    match fs::remove_dir_all(CACHE_DIR) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+// The output of the exercise from the last time it passed,
+// if nothing it depends on has changed since
+pub fn lookup(exercise: &Exercise) -> Option<ExerciseOutput> {
+    let path = entry(exercise)?;
+    let cached = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&cached).ok()
+}
+
+// Remember that the exercise passed with the given output.
+// Failing to do so only makes the next check slower, so it isn't an error.
+pub fn store(exercise: &Exercise, output: &ExerciseOutput) {
+    if let Some(path) = entry(exercise) {
+        let serialized = serde_json::to_string(output).expect("Failed to serialize the output");
+        let _ = fs::create_dir_all(CACHE_DIR).and_then(|_| fs::write(path, serialized));
+    }
+}
+
+// Compile and run the exercise, unless it passed with the same inputs before
+pub fn compile_and_run(exercise: &Exercise) -> Result<ExerciseOutput, RustlingsError> {
+    if let Some(output) = lookup(exercise) {
+        return Ok(output);
+    }
+    let output = exercise.compile().and_then(|compiled| compiled.run())?;
+    store(exercise, &output);
+    Ok(output)
+}
+
+// The file the result of the exercise is cached in. It is named after
+// everything that can change the result: the source code, the manifest
+// entry, the compiler flags and the versions of rustlings and rustc.
+fn entry(exercise: &Exercise) -> Option<PathBuf> {
+    if DISABLED.load(Ordering::SeqCst) {
+        return None;
+    }
+    let rustc = rustc_version()?;
+    let mut key = format!(
+        "{}\n{rustc}\n{exercise:?}\n{COMPILER_FLAGS:?}\n",
+        env!("CARGO_PKG_VERSION")
+    )
+    .into_bytes();
+    key.extend(fs::read(&exercise.path).ok()?);
+    if exercise.mode == Mode::BuildScript {
+        key.extend(fs::read(exercise.path.with_file_name("build.rs")).ok()?);
+    }
+    Some(Path::new(CACHE_DIR).join(format!("{}.json", hash_bytes(&key))))
+}
+
+// The version of the compiler the exercises are built with,
+// looked up once per process
+fn rustc_version() -> Option<&'static str> {
+    static VERSION: OnceLock<Option<String>> = OnceLock::new();
+    VERSION
+        .get_or_init(|| {
+            let output = Command::new("rustc").arg("--version").output().ok()?;
+            output
+                .status
+                .success()
+                .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        })
+        .as_deref()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::progress::STATE_DIR;
+
+    #[test]
+    fn test_key_depends_on_mode() {
+        let compile = Exercise {
+            name: "finished_exercise".into(),
+            path: PathBuf::from("tests/fixture/state/finished_exercise.rs"),
+            ..Default::default()
+        };
+        let test = Exercise {
+            mode: Mode::Test,
+            ..compile.clone()
+        };
+        assert_eq!(entry(&compile), entry(&compile.clone()));
+        assert_ne!(entry(&compile), entry(&test));
+        assert!(entry(&compile).unwrap().starts_with(STATE_DIR));
+    }
+}
@@ -0,0 +1,315 @@
+use crate::cache;
+use crate::diagnostics::Diagnostic;
+use crate::exercise::{Exercise, ExerciseOutput, Mode};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+use tokio::sync::Semaphore;
+
+#[derive(Deserialize, Serialize)]
+pub struct ExerciseCheckList {
+    pub exercises: Vec<ExerciseResult>,
+    pub user_name: Option<String>,
+    pub statistics: ExerciseStatistics,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ExerciseResult {
+    pub name: String,
+    pub result: bool,
+    pub mode: Mode,
+    // How long grading the exercise took, in milliseconds
+    pub duration_ms: u64,
+    // The exit code of the last command that ran, if it exited normally
+    pub exit_status: Option<i32>,
+    // The compiler or test output of the last command that ran
+    pub output: String,
+    // The messages of the compiler, if it failed
+    #[serde(default)]
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ExerciseStatistics {
+    pub total_exercations: usize,
+    pub total_succeeds: usize,
+    pub total_failures: usize,
+    pub total_time: u32,
+}
+
+// The formats a grading report can be written in
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ReportFormat {
+    Json,
+    Junit,
+    Tap,
+    Markdown,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(ReportFormat::Json),
+            "junit" => Ok(ReportFormat::Junit),
+            "tap" => Ok(ReportFormat::Tap),
+            "markdown" => Ok(ReportFormat::Markdown),
+            _ => Err(format!(
+                "unknown report format `{s}`, expected one of json, junit, tap or markdown"
+            )),
+        }
+    }
+}
+
+// The number of jobs used when none is requested: one per available CPU
+pub fn default_jobs() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+// Grade all exercises, running at most `jobs` of them at the same time.
+// Every job compiles into its own scratch directory, so the outcome is the
+// same as the one of a serial run, and results are reported in the order
+// of the given exercises regardless of which job finishes first.
+pub async fn grade(exercises: Vec<Exercise>, jobs: usize) -> ExerciseCheckList {
+    let start = Instant::now();
+    let total = exercises.len();
+    let pool = Arc::new(Semaphore::new(jobs.max(1)));
+
+    let tasks: Vec<_> = exercises
+        .into_iter()
+        .map(|exercise| {
+            let pool = Arc::clone(&pool);
+            tokio::spawn(async move {
+                let _permit = pool.acquire_owned().await.unwrap();
+                // Compiling and running exercises blocks, keep it off the async workers
+                tokio::task::spawn_blocking(move || grade_exercise(&exercise))
+                    .await
+                    .unwrap()
+            })
+        })
+        .collect();
+
+    let mut check_list = ExerciseCheckList {
+        exercises: Vec::with_capacity(total),
+        user_name: None,
+        statistics: ExerciseStatistics {
+            total_exercations: total,
+            total_succeeds: 0,
+            total_failures: 0,
+            total_time: 0,
+        },
+    };
+    for task in tasks {
+        let result = task.await.unwrap();
+        if result.result {
+            check_list.statistics.total_succeeds += 1;
+            println!("{}执行成功", result.name);
+        } else {
+            check_list.statistics.total_failures += 1;
+            println!("{}执行失败", result.name);
+        }
+        println!("总的题目数: {total}");
+        println!(
+            "当前做正确的题目数: {}",
+            check_list.statistics.total_succeeds
+        );
+        println!("当前修改试卷耗时: {} s", result.duration_ms / 1000);
+        check_list.exercises.push(result);
+    }
+    check_list.statistics.total_time = start.elapsed().as_secs() as u32;
+    check_list
+}
+
+// Check whether the exercise compiles and runs successfully, like
+// `rustlings run` does, capturing the output instead of printing it.
+fn grade_exercise(exercise: &Exercise) -> ExerciseResult {
+    let start = Instant::now();
+    let (result, output) = match cache::compile_and_run(exercise) {
+        Ok(output) => (true, output),
+        Err(err) => match err.output() {
+            Some(output) => (false, output.clone()),
+            // Nothing ran, so the error itself is all there is to report
+            None => (
+                false,
+                ExerciseOutput {
+                    stdout: String::new(),
+                    stderr: err.to_string(),
+                    status: None,
+                    limit_exceeded: None,
+                    diagnostics: Vec::new(),
+                },
+            ),
+        },
+    };
+    ExerciseResult {
+        name: exercise.name.clone(),
+        result,
+        mode: exercise.mode,
+        duration_ms: start.elapsed().as_millis() as u64,
+        exit_status: output.status,
+        output: captured_text(exercise, &output),
+        diagnostics: output.diagnostics,
+    }
+}
+
+// Merge both streams of a command's output, without terminal colors
+fn captured_text(exercise: &Exercise, output: &ExerciseOutput) -> String {
+    let mut text = match (output.stdout.trim_end(), output.stderr.trim_end()) {
+        (stdout, "") => stdout.to_string(),
+        ("", stderr) => stderr.to_string(),
+        (stdout, stderr) => format!("{stdout}\n{stderr}"),
+    };
+    if let Some(limit) = output.limit_exceeded {
+        text += &format!("\nerror: stopped, {limit}");
+    }
+    if let Some(mismatch) = exercise.mismatch(output) {
+        text += &format!("\nerror: {mismatch}");
+        if let Some(diff) = mismatch.diff() {
+            text += &format!("\n{}", diff.trim_end());
+        }
+    }
+    console::strip_ansi_codes(&text).to_string()
+}
+
+impl ExerciseCheckList {
+    // Write the report to the given path, creating its directory if needed
+    pub fn write(&self, path: &Path, format: ReportFormat) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.render(format))
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Json => {
+                serde_json::to_string_pretty(self).expect("Failed to serialize the report")
+            }
+            ReportFormat::Junit => self.to_junit(),
+            ReportFormat::Tap => self.to_tap(),
+            ReportFormat::Markdown => self.to_markdown(),
+        }
+    }
+
+    fn to_junit(&self) -> String {
+        let stats = &self.statistics;
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuites name=\"rustlings\" tests=\"{}\" failures=\"{}\" time=\"{}\">",
+            stats.total_exercations, stats.total_failures, stats.total_time
+        );
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"rustlings\" tests=\"{}\" failures=\"{}\" time=\"{}\">",
+            stats.total_exercations, stats.total_failures, stats.total_time
+        );
+        for exercise in &self.exercises {
+            let _ = write!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"rustlings.{}\" time=\"{:.3}\"",
+                xml_escape(&exercise.name),
+                exercise.mode,
+                exercise.duration_ms as f64 / 1000.0
+            );
+            if exercise.result {
+                let _ = writeln!(
+                    xml,
+                    ">\n      <system-out>{}</system-out>\n    </testcase>",
+                    xml_escape(&exercise.output)
+                );
+            } else {
+                let message = match exercise.exit_status {
+                    Some(code) => format!("exited with status {code}"),
+                    None => "terminated by a signal".to_string(),
+                };
+                let _ = writeln!(
+                    xml,
+                    ">\n      <failure message=\"{}\">{}</failure>\n    </testcase>",
+                    message,
+                    xml_escape(&exercise.output)
+                );
+            }
+        }
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+
+    fn to_tap(&self) -> String {
+        let mut tap = String::from("TAP version 13\n");
+        let _ = writeln!(tap, "1..{}", self.exercises.len());
+        for (i, exercise) in self.exercises.iter().enumerate() {
+            let status = if exercise.result { "ok" } else { "not ok" };
+            let _ = writeln!(tap, "{status} {} - {}", i + 1, exercise.name);
+            if !exercise.result {
+                tap.push_str("  ---\n");
+                let _ = writeln!(tap, "  mode: {}", exercise.mode);
+                let _ = writeln!(tap, "  duration_ms: {}", exercise.duration_ms);
+                if let Some(code) = exercise.exit_status {
+                    let _ = writeln!(tap, "  exit_status: {code}");
+                }
+                tap.push_str("  output: |\n");
+                for line in exercise.output.lines() {
+                    let _ = writeln!(tap, "    {line}");
+                }
+                tap.push_str("  ...\n");
+            }
+        }
+        tap
+    }
+
+    fn to_markdown(&self) -> String {
+        let stats = &self.statistics;
+        let mut md = String::from("# Rustlings grading report\n\n");
+        let _ = writeln!(
+            md,
+            "{} of {} exercises passed in {} s.\n",
+            stats.total_succeeds, stats.total_exercations, stats.total_time
+        );
+        md.push_str("| Exercise | Mode | Result | Time (ms) |\n");
+        md.push_str("|----------|------|--------|-----------|\n");
+        for exercise in &self.exercises {
+            let _ = writeln!(
+                md,
+                "| {} | {} | {} | {} |",
+                exercise.name,
+                exercise.mode,
+                if exercise.result { "pass" } else { "fail" },
+                exercise.duration_ms
+            );
+        }
+        for exercise in self.exercises.iter().filter(|e| !e.result) {
+            let _ = write!(
+                md,
+                "\n## {}\n\n```text\n{}\n```\n",
+                exercise.name, exercise.output
+            );
+        }
+        md
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.chars()
+        .filter(|&c| matches!(c, '\t' | '\n' | '\r') || !c.is_control())
+        .fold(String::with_capacity(text.len()), |mut escaped, c| {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&apos;"),
+                c => escaped.push(c),
+            }
+            escaped
+        })
+}
@@ -0,0 +1,304 @@
+use console::style;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+
+// A message of the compiler, as printed by `rustc --error-format=json`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub message: String,
+    pub code: Option<Code>,
+    // `error`, `warning`, `note`, `help` or `failure-note`
+    pub level: String,
+    pub spans: Vec<Span>,
+    // Notes and help attached to the message
+    pub children: Vec<Diagnostic>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Code {
+    // Like `E0308` or `clippy::float_cmp`
+    pub code: String,
+}
+
+// A part of the source code a diagnostic is about
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Span {
+    pub file_name: String,
+    pub line_start: usize,
+    pub line_end: usize,
+    pub column_start: usize,
+    pub column_end: usize,
+    pub is_primary: bool,
+    // The source lines of the span
+    pub text: Vec<SpanLine>,
+    pub label: Option<String>,
+    // The code that should replace the span, for suggestions
+    pub suggested_replacement: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SpanLine {
+    pub text: String,
+    // The columns of the span on this line, counted in characters from 1
+    pub highlight_start: usize,
+    pub highlight_end: usize,
+}
+
+// The messages cargo prints with `--message-format=json`,
+// only the ones from the compiler are of interest
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Option<Diagnostic>,
+}
+
+impl Diagnostic {
+    fn is_error(&self) -> bool {
+        self.level.starts_with("error")
+    }
+
+    // The summaries rustc ends with, like `aborting due to 2 previous errors`,
+    // are replaced by our own
+    fn is_summary(&self) -> bool {
+        self.spans.is_empty()
+            && (self.level == "failure-note"
+                || self.message.starts_with("aborting due to")
+                || self.message.ends_with("emitted"))
+    }
+}
+
+// Split the output of `rustc --error-format=json` into its diagnostics
+// and the lines that aren't diagnostics, like linker errors
+pub fn parse_rustc(stderr: &str) -> (Vec<Diagnostic>, String) {
+    let mut diagnostics = Vec::new();
+    let mut rest = String::new();
+    for line in stderr.lines() {
+        match serde_json::from_str(line) {
+            Ok(diagnostic) => diagnostics.push(diagnostic),
+            Err(_) => {
+                rest += line;
+                rest += "\n";
+            }
+        }
+    }
+    (prepare(diagnostics), rest)
+}
+
+// Split the output of `cargo --message-format=json` into the
+// diagnostics of the compiler and the lines that aren't messages
+pub fn parse_cargo(stdout: &str) -> (Vec<Diagnostic>, String) {
+    let mut diagnostics = Vec::new();
+    let mut rest = String::new();
+    for line in stdout.lines() {
+        match serde_json::from_str::<CargoMessage>(line) {
+            Ok(message) => {
+                if message.reason == "compiler-message" {
+                    diagnostics.extend(message.message);
+                }
+            }
+            Err(_) => {
+                rest += line;
+                rest += "\n";
+            }
+        }
+    }
+    (prepare(diagnostics), rest)
+}
+
+// Drop the summaries and put the errors before the warnings. rustc reports
+// the errors of earlier passes first, which the later ones often follow
+// from, so the order of the errors is kept.
+fn prepare(mut diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+    diagnostics.retain(|diagnostic| !diagnostic.is_summary());
+    diagnostics.sort_by_key(|diagnostic| !diagnostic.is_error());
+    diagnostics
+}
+
+// The codes of the errors, for every error without one an empty string.
+// Lints that are denied have their name as code instead.
+pub fn error_codes(diagnostics: &[Diagnostic]) -> Vec<&str> {
+    diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.is_error())
+        .map(|diagnostic| {
+            diagnostic
+                .code
+                .as_ref()
+                .map_or("", |code| code.code.as_str())
+        })
+        .collect()
+}
+
+// The code of the first error with one `rustc --explain` knows, like `E0308`
+pub fn first_error_code(diagnostics: &[Diagnostic]) -> Option<&str> {
+    error_codes(diagnostics)
+        .into_iter()
+        .find(|code| code.starts_with('E'))
+}
+
+// Render the diagnostics with the source lines they are about,
+// followed by a line counting the errors and warnings
+pub fn render(diagnostics: &[Diagnostic]) -> String {
+    let mut out = String::new();
+    for diagnostic in diagnostics {
+        render_diagnostic(&mut out, diagnostic);
+        out.push('\n');
+    }
+
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    let warnings = diagnostics.iter().filter(|d| d.level == "warning").count();
+    let count = |n: usize, what: &str| match n {
+        1 => format!("1 {what}"),
+        _ => format!("{n} {what}s"),
+    };
+    let summary = match (errors, warnings) {
+        (0, 0) => return out,
+        (0, _) => count(warnings, "warning"),
+        (_, 0) => count(errors, "error"),
+        _ => format!(
+            "{} and {}",
+            count(errors, "error"),
+            count(warnings, "warning")
+        ),
+    };
+    let _ = writeln!(out, "{}", style(summary).bold());
+    out
+}
+
+fn render_diagnostic(out: &mut String, diagnostic: &Diagnostic) {
+    let code = diagnostic
+        .code
+        .as_ref()
+        .filter(|code| code.code.starts_with('E'))
+        .map(|code| format!("[{}]", code.code))
+        .unwrap_or_default();
+    let header = format!("{}{code}", diagnostic.level);
+    let header = if diagnostic.is_error() {
+        style(header).red().bold()
+    } else if diagnostic.level == "warning" {
+        style(header).yellow().bold()
+    } else {
+        style(header).bold()
+    };
+    let _ = writeln!(out, "{header}: {}", style(&diagnostic.message).bold());
+
+    // The primary span comes first, the others add context to it
+    let mut spans: Vec<&Span> = diagnostic.spans.iter().collect();
+    spans.sort_by_key(|span| !span.is_primary);
+    if let Some(primary) = spans.first() {
+        let _ = writeln!(
+            out,
+            "  {} {}:{}:{}",
+            style("-->").blue(),
+            primary.file_name,
+            primary.line_start,
+            primary.column_start
+        );
+    }
+    let width = spans
+        .iter()
+        .map(|span| span.line_end.to_string().len())
+        .max()
+        .unwrap_or(0);
+    let mut last_line = None;
+    for span in spans {
+        // Spans on the line shown last are marked below it as well
+        let line = (&span.file_name, span.line_start);
+        let show_first_line = last_line != Some(line);
+        render_span(out, span, width, diagnostic.is_error(), show_first_line);
+        last_line = Some((&span.file_name, span.line_end));
+    }
+
+    for child in &diagnostic.children {
+        let suggestion = child
+            .spans
+            .iter()
+            .find_map(|span| span.suggested_replacement.as_ref())
+            .filter(|replacement| !replacement.is_empty())
+            .map(|replacement| format!(": `{replacement}`"))
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "{:width$} {} {}: {}{suggestion}",
+            "",
+            style("=").blue(),
+            style(&child.level).bold(),
+            child.message
+        );
+    }
+}
+
+// The source lines of the span, with the span marked below them
+fn render_span(out: &mut String, span: &Span, width: usize, is_error: bool, show_first_line: bool) {
+    let gutter = style("|").blue();
+    for (number, line) in (span.line_start..).zip(&span.text) {
+        if number != span.line_start || show_first_line {
+            let _ = writeln!(
+                out,
+                "{} {gutter}  {}",
+                style(format!("{number:>width$}")).blue().bold(),
+                line.text
+            );
+        }
+
+        // Tabs are kept, so the marks line up with the source
+        let indent: String = line
+            .text
+            .chars()
+            .take(line.highlight_start.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let length = line
+            .highlight_end
+            .saturating_sub(line.highlight_start)
+            .max(1);
+        let mark = if span.is_primary { "^" } else { "-" }.repeat(length);
+        let is_last = number == span.line_end;
+        let label = span
+            .label
+            .as_deref()
+            .filter(|_| is_last)
+            .unwrap_or_default();
+        let marked = format!("{mark} {label}");
+        let marked = match (span.is_primary, is_error) {
+            (true, true) => style(marked.trim_end()).red().bold(),
+            (true, false) => style(marked.trim_end()).yellow().bold(),
+            (false, _) => style(marked.trim_end()).blue(),
+        };
+        let _ = writeln!(out, "{:width$} {gutter}  {indent}{marked}", "");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const STDERR: &str = r#"{"$message_type":"diagnostic","message":"unused variable: `unused`","code":{"code":"unused_variables","explanation":null},"level":"warning","spans":[{"file_name":"e.rs","byte_start":81,"byte_end":87,"line_start":4,"line_end":4,"column_start":9,"column_end":15,"is_primary":true,"text":[{"text":"    let unused = 1;","highlight_start":9,"highlight_end":15}],"label":null,"suggested_replacement":null,"suggestion_applicability":null,"expansion":null}],"children":[],"rendered":""}
+{"$message_type":"diagnostic","message":"mismatched types","code":{"code":"E0308","explanation":""},"level":"error","spans":[{"file_name":"e.rs","byte_start":29,"byte_end":32,"line_start":2,"line_end":2,"column_start":18,"column_end":21,"is_primary":true,"text":[{"text":"    let x: i32 = \"s\";","highlight_start":18,"highlight_end":21}],"label":"expected `i32`, found `&str`","suggested_replacement":null,"suggestion_applicability":null,"expansion":null},{"file_name":"e.rs","byte_start":23,"byte_end":26,"line_start":2,"line_end":2,"column_start":12,"column_end":15,"is_primary":false,"text":[{"text":"    let x: i32 = \"s\";","highlight_start":12,"highlight_end":15}],"label":"expected due to this","suggested_replacement":null,"suggestion_applicability":null,"expansion":null}],"children":[],"rendered":""}
+{"$message_type":"diagnostic","message":"aborting due to 1 previous error; 1 warning emitted","code":null,"level":"error","spans":[],"children":[],"rendered":""}
+"#;
+
+    #[test]
+    fn test_render_puts_errors_first() {
+        let (diagnostics, rest) = parse_rustc(STDERR);
+        assert_eq!(rest, "");
+        assert_eq!(error_codes(&diagnostics), ["E0308"]);
+        assert_eq!(first_error_code(&diagnostics), Some("E0308"));
+        assert_eq!(
+            console::strip_ansi_codes(&render(&diagnostics)),
+            "error[E0308]: mismatched types
+  --> e.rs:2:18
+2 |      let x: i32 = \"s\";
+  |                   ^^^ expected `i32`, found `&str`
+  |             --- expected due to this
+
+warning: unused variable: `unused`
+  --> e.rs:4:9
+4 |      let unused = 1;
+  |          ^^^^^^
+
+1 error and 1 warning
+"
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use console::style;
+use std::fmt::Write as _;
+
+// How many unchanged lines are shown around every change
+const CONTEXT: usize = 3;
+
+// A line of a line-by-line comparison of two texts
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Line<'a> {
+    // The line is in both texts
+    Same(&'a str),
+    // The line is only in the old text
+    Removed(&'a str),
+    // The line is only in the new text
+    Added(&'a str),
+}
+
+impl Line<'_> {
+    fn is_change(&self) -> bool {
+        !matches!(self, Line::Same(_))
+    }
+}
+
+// Compare two texts line by line, keeping as many lines in common as possible
+pub fn lines<'a>(old: &'a str, new: &'a str) -> Vec<Line<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // common[i][j] is the length of the longest common subsequence
+    // of old[i..] and new[j..]
+    let mut common = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(Line::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            diff.push(Line::Removed(old[i]));
+            i += 1;
+        } else {
+            diff.push(Line::Added(new[j]));
+            j += 1;
+        }
+    }
+    diff.extend(old[i..].iter().map(|line| Line::Removed(line)));
+    diff.extend(new[j..].iter().map(|line| Line::Added(line)));
+    diff
+}
+
+// Render the changes from `old` to `new` as a colored unified diff,
+// or an empty string if there are none
+pub fn unified(old_label: &str, new_label: &str, old: &str, new: &str) -> String {
+    let diff = lines(old, new);
+    let changes: Vec<usize> = (0..diff.len()).filter(|&i| diff[i].is_change()).collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", style(format!("--- {old_label}")).bold());
+    let _ = writeln!(out, "{}", style(format!("+++ {new_label}")).bold());
+
+    // The line numbers in the old and new text each diff line starts at
+    let mut positions = Vec::with_capacity(diff.len() + 1);
+    let (mut old_line, mut new_line) = (0, 0);
+    for line in &diff {
+        positions.push((old_line, new_line));
+        match line {
+            Line::Same(_) => {
+                old_line += 1;
+                new_line += 1;
+            }
+            Line::Removed(_) => old_line += 1,
+            Line::Added(_) => new_line += 1,
+        }
+    }
+    positions.push((old_line, new_line));
+
+    let mut i = 0;
+    while i < changes.len() {
+        // Changes closer together than twice the context share a hunk
+        let first = changes[i];
+        let mut last = first;
+        while i + 1 < changes.len() && changes[i + 1] - last <= 2 * CONTEXT {
+            i += 1;
+            last = changes[i];
+        }
+        i += 1;
+        let start = first.saturating_sub(CONTEXT);
+        let end = (last + CONTEXT + 1).min(diff.len());
+
+        let (old_start, new_start) = positions[start];
+        let (old_end, new_end) = positions[end];
+        let _ = writeln!(
+            out,
+            "{}",
+            style(format!(
+                "@@ -{} +{} @@",
+                hunk_range(old_start, old_end - old_start),
+                hunk_range(new_start, new_end - new_start)
+            ))
+            .cyan()
+        );
+        for line in &diff[start..end] {
+            let _ = match line {
+                Line::Same(text) => writeln!(out, " {text}"),
+                Line::Removed(text) => writeln!(out, "{}", style(format!("-{text}")).red()),
+                Line::Added(text) => writeln!(out, "{}", style(format!("+{text}")).green()),
+            };
+        }
+    }
+    out
+}
+
+// A hunk range like `3,4`, the start of an empty range is the line before it
+fn hunk_range(start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{count}", start + 1),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_identical_texts_have_no_diff() {
+        assert_eq!(unified("a", "b", "one\ntwo\n", "one\ntwo\n"), "");
+    }
+
+    #[test]
+    fn test_lines_keeps_common_lines() {
+        assert_eq!(
+            lines("a\nb\nc", "a\nx\nc"),
+            [
+                Line::Same("a"),
+                Line::Removed("b"),
+                Line::Added("x"),
+                Line::Same("c")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unified_hunks() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n";
+        let new = "1\n2\n3\n4\n5\nsix\n7\n8\n9\n10\n11\n12\n13\n";
+        let diff = unified("old", "new", old, new);
+        assert_eq!(
+            console::strip_ansi_codes(&diff),
+            "--- old\n+++ new\n\
+             @@ -3,7 +3,7 @@\n 3\n 4\n 5\n-6\n+six\n 7\n 8\n 9\n\
+             @@ -10,3 +10,4 @@\n 10\n 11\n 12\n+13\n"
+        );
+    }
+}
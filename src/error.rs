@@ -0,0 +1,222 @@
+use crate::exercise::ExerciseOutput;
+use std::fmt::{self, Display, Formatter};
+use std::io;
+
+// Everything that can go wrong while checking an exercise.
+// The variants up to `LimitExceeded` mean the learner's code isn't right yet,
+// the remaining ones mean the environment rustlings runs in is broken,
+// or that it was asked to do something it can't.
+#[derive(Debug)]
+pub enum RustlingsError {
+    // The exercise works, but still contains its `I AM NOT DONE` marker
+    Pending {
+        exercise: String,
+    },
+    // The exercise doesn't compile, or Clippy rejects it
+    CompileFailure {
+        exercise: String,
+        output: ExerciseOutput,
+    },
+    // A compile_fail mode exercise compiles
+    CompiledUnexpectedly {
+        exercise: String,
+        output: ExerciseOutput,
+    },
+    // The tests of the exercise fail
+    TestFailure {
+        exercise: String,
+        output: ExerciseOutput,
+    },
+    // The exercise binary exits unsuccessfully
+    RuntimeFailure {
+        exercise: String,
+        output: ExerciseOutput,
+    },
+    // The exercise printed or exited with something else than info.toml expects
+    WrongOutput {
+        exercise: String,
+        output: ExerciseOutput,
+    },
+    // The exercise has to be formatted like rustfmt does, but isn't.
+    // The output holds the changes rustfmt would make.
+    Unformatted {
+        exercise: String,
+        output: ExerciseOutput,
+    },
+    // The benchmark of a perf mode exercise took longer than its `max_duration`.
+    // The output tells how long it took.
+    TooSlow {
+        exercise: String,
+        output: ExerciseOutput,
+    },
+    // The exercise ran for longer than its timeout and was killed
+    Timeout {
+        exercise: String,
+        output: ExerciseOutput,
+    },
+    // The exercise used more memory or output than it may and was killed
+    LimitExceeded {
+        exercise: String,
+        output: ExerciseOutput,
+    },
+    // A tool of the Rust toolchain couldn't be found
+    ToolchainMissing {
+        tool: String,
+    },
+    // Reading or writing a file, or starting a process failed
+    Io {
+        context: String,
+        source: io::Error,
+    },
+    // info.toml can't be read or doesn't describe valid exercises
+    Manifest {
+        message: String,
+    },
+    // The command line asks for something that doesn't exist or can't be
+    // done, like an exercise or track that isn't there
+    Usage {
+        message: String,
+    },
+    // None of the tests of the exercise match the filter they were run with
+    NoMatchingTests {
+        exercise: String,
+        filter: String,
+    },
+}
+
+impl RustlingsError {
+    // The error for a tool of the toolchain that couldn't be started
+    pub fn spawn(tool: &str, source: io::Error) -> RustlingsError {
+        if source.kind() == io::ErrorKind::NotFound {
+            RustlingsError::ToolchainMissing {
+                tool: tool.to_string(),
+            }
+        } else {
+            RustlingsError::io(format!("Failed to run `{tool}`"), source)
+        }
+    }
+
+    pub fn usage(message: impl Into<String>) -> RustlingsError {
+        RustlingsError::Usage {
+            message: message.into(),
+        }
+    }
+
+    pub fn io(context: impl Into<String>, source: io::Error) -> RustlingsError {
+        RustlingsError::Io {
+            context: context.into(),
+            source,
+        }
+    }
+
+    // The exit code rustlings terminates with because of this error
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RustlingsError::Pending { .. } => 1,
+            RustlingsError::CompileFailure { .. } => 2,
+            RustlingsError::TestFailure { .. } => 3,
+            RustlingsError::RuntimeFailure { .. } => 4,
+            RustlingsError::Timeout { .. } => 5,
+            RustlingsError::LimitExceeded { .. } => 6,
+            RustlingsError::WrongOutput { .. } => 7,
+            RustlingsError::Unformatted { .. } => 8,
+            RustlingsError::TooSlow { .. } => 9,
+            RustlingsError::CompiledUnexpectedly { .. } => 10,
+            RustlingsError::ToolchainMissing { .. } => 20,
+            RustlingsError::Io { .. } => 21,
+            RustlingsError::Manifest { .. } => 22,
+            RustlingsError::Usage { .. } => 23,
+            RustlingsError::NoMatchingTests { .. } => 23,
+        }
+    }
+
+    // The name of the exercise the error is about, if it is about one
+    pub fn exercise(&self) -> Option<&str> {
+        match self {
+            RustlingsError::Pending { exercise }
+            | RustlingsError::CompileFailure { exercise, .. }
+            | RustlingsError::CompiledUnexpectedly { exercise, .. }
+            | RustlingsError::TestFailure { exercise, .. }
+            | RustlingsError::RuntimeFailure { exercise, .. }
+            | RustlingsError::WrongOutput { exercise, .. }
+            | RustlingsError::Unformatted { exercise, .. }
+            | RustlingsError::TooSlow { exercise, .. }
+            | RustlingsError::Timeout { exercise, .. }
+            | RustlingsError::LimitExceeded { exercise, .. }
+            | RustlingsError::NoMatchingTests { exercise, .. } => Some(exercise),
+            _ => None,
+        }
+    }
+
+    // The output of the command that failed, if one ran
+    pub fn output(&self) -> Option<&ExerciseOutput> {
+        match self {
+            RustlingsError::CompileFailure { output, .. }
+            | RustlingsError::CompiledUnexpectedly { output, .. }
+            | RustlingsError::TestFailure { output, .. }
+            | RustlingsError::RuntimeFailure { output, .. }
+            | RustlingsError::WrongOutput { output, .. }
+            | RustlingsError::Unformatted { output, .. }
+            | RustlingsError::TooSlow { output, .. }
+            | RustlingsError::Timeout { output, .. }
+            | RustlingsError::LimitExceeded { output, .. } => Some(output),
+            _ => None,
+        }
+    }
+}
+
+impl Display for RustlingsError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            RustlingsError::Pending { exercise } => {
+                write!(f, "{exercise} still contains its `I AM NOT DONE` comment")
+            }
+            RustlingsError::CompileFailure { exercise, .. } => {
+                write!(f, "{exercise} failed to compile")
+            }
+            RustlingsError::CompiledUnexpectedly { exercise, .. } => {
+                write!(f, "{exercise} compiles, but the compiler should reject it")
+            }
+            RustlingsError::TestFailure { exercise, .. } => {
+                write!(f, "the tests of {exercise} failed")
+            }
+            RustlingsError::RuntimeFailure { exercise, .. } => {
+                write!(f, "{exercise} exited with an error")
+            }
+            RustlingsError::WrongOutput { exercise, .. } => {
+                write!(f, "{exercise} didn't print or exit as expected")
+            }
+            RustlingsError::Unformatted { exercise, .. } => {
+                write!(f, "{exercise} isn't formatted the way rustfmt formats it")
+            }
+            RustlingsError::TooSlow { exercise, .. } => {
+                write!(f, "the benchmark of {exercise} took longer than its budget")
+            }
+            RustlingsError::Timeout { exercise, output }
+            | RustlingsError::LimitExceeded { exercise, output } => match output.limit_exceeded {
+                Some(limit) => write!(f, "{exercise} was stopped, {limit}"),
+                None => write!(f, "{exercise} was stopped"),
+            },
+            RustlingsError::ToolchainMissing { tool } => write!(
+                f,
+                "We cannot find `{tool}`. Try running `{tool} --version` to diagnose your problem. \
+                 For instructions on how to install Rust, check the README."
+            ),
+            RustlingsError::Io { context, source } => write!(f, "{context}: {source}"),
+            RustlingsError::Manifest { message } => write!(f, "Invalid info.toml: {message}"),
+            RustlingsError::Usage { message } => write!(f, "{message}"),
+            RustlingsError::NoMatchingTests { exercise, filter } => {
+                write!(f, "no test of {exercise} matches `{filter}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RustlingsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RustlingsError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -1,35 +1,114 @@
+use crate::diagnostics::{self, Diagnostic};
+use crate::error::RustlingsError;
+use crate::expected::{self, ExpectedStdout, Mismatch};
+use crate::libtest;
+use crate::limits::{self, LimitExceeded, Limits};
+use crate::rustfmt;
 use regex::Regex;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
 use std::fmt::{self, Display, Formatter};
-use std::fs::{self, remove_file, File};
-use std::io::Read;
-use std::path::PathBuf;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 use std::process::{self, Command};
+use std::slice;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
-const RUSTC_COLOR_ARGS: &[&str] = &["--color", "always"];
+// The diagnostics are rendered by us, see `diagnostics::render`
+const RUSTC_JSON_ARGS: &[&str] = &["--error-format=json"];
+const CARGO_JSON_ARGS: &[&str] = &["--message-format=json"];
 const RUSTC_EDITION_ARGS: &[&str] = &["--edition", "2021"];
-const I_AM_DONE_REGEX: &str = r"(?m)^\s*///?\s*I\s+AM\s+NOT\s+DONE";
+const CLIPPY_ARGS: &[&str] = &["--", "-D", "warnings", "-D", "clippy::float_cmp"];
+// Perf mode exercises are timed the way they would run in a release build
+const OPTIMIZE_ARGS: &[&str] = &["-O"];
+// All flags the tools are run with, the results of exercises depend on them
+pub const COMPILER_FLAGS: [&[&str]; 5] = [
+    RUSTC_JSON_ARGS,
+    CARGO_JSON_ARGS,
+    RUSTC_EDITION_ARGS,
+    CLIPPY_ARGS,
+    OPTIMIZE_ARGS,
+];
+pub const I_AM_DONE_REGEX: &str = r"(?m)^\s*///?\s*I\s+AM\s+NOT\s+DONE";
 const CONTEXT: usize = 2;
-const CLIPPY_CARGO_TOML_PATH: &str = "./exercises/clippy/Cargo.toml";
-const BUILD_SCRIPT_CARGO_TOML_PATH: &str = "./exercises/tests/Cargo.toml";
-
-// Get a temporary file name that is hopefully unique
-#[inline]
-fn temp_file() -> String {
-    let thread_id: String = format!("{:?}", std::thread::current().id())
-        .chars()
-        .filter(|c| c.is_alphanumeric())
-        .collect();
-
-    format!("./temp_{}_{thread_id}", process::id())
+// How often the benchmark of a perf mode exercise runs, the median run counts
+const BENCHMARK_RUNS: usize = 5;
+
+// Counts the scratch directories created by this process, so that each one gets its own name
+static SCRATCH_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// A private directory holding everything one compilation produces:
+// the binary, and for cargo based modes the manifest and target directory.
+// Every compilation gets a fresh one, so exercises compiled at the same time
+// can never overwrite each other's files. It is removed when dropped.
+struct ScratchDir {
+    path: PathBuf,
+}
+
+impl ScratchDir {
+    fn new() -> io::Result<ScratchDir> {
+        let id = SCRATCH_COUNTER.fetch_add(1, Ordering::SeqCst);
+        let path = env::temp_dir().join(format!("rustlings_{}_{id}", process::id()));
+        fs::create_dir_all(&path)?;
+        Ok(ScratchDir { path })
+    }
+
+    // The path the compiled binary is written to
+    fn binary(&self) -> PathBuf {
+        self.path.join("exercise")
+    }
+
+    // The path the exercise is written to when it is compiled as a library,
+    // where rustdoc finds it for the examples in its documentation
+    fn library(&self, crate_name: &str) -> PathBuf {
+        self.path.join(format!("lib{crate_name}.rlib"))
+    }
+
+    // Write a cargo manifest building the exercise, returning its path
+    fn write_manifest(
+        &self,
+        exercise: &Exercise,
+        build_script: Option<&Path>,
+    ) -> io::Result<PathBuf> {
+        let mut cargo_toml = format!(
+            r#"[package]
+name = "{}"
+version = "0.0.1"
+edition = "2021"
+"#,
+            exercise.name
+        );
+        if let Some(build_script) = build_script {
+            cargo_toml += &format!("build = {:?}\n", fs::canonicalize(build_script)?);
+        }
+        cargo_toml += &format!(
+            r#"[[bin]]
+name = "{}"
+path = {:?}"#,
+            exercise.name,
+            fs::canonicalize(&exercise.path)?
+        );
+        let manifest = self.path.join("Cargo.toml");
+        fs::write(&manifest, cargo_toml)?;
+        Ok(manifest)
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ignored = fs::remove_dir_all(&self.path);
+    }
 }
 
 // The mode of the exercise.
-#[derive(Deserialize, Copy, Clone, Debug)]
+#[derive(Deserialize, Serialize, Copy, Clone, Debug, Default, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum Mode {
     // Indicates that the exercise should be compiled as a binary
+    #[default]
     Compile,
     // Indicates that the exercise should be compiled as a test harness
     Test,
@@ -37,25 +116,113 @@ pub enum Mode {
     Clippy,
     // Indicates that the exercise should be run using cargo with build script
     BuildScript,
+    // Indicates that the examples in the documentation of the exercise should be tested
+    Doctest,
+    // Indicates that the compiler should reject the exercise with one of its `error_codes`
+    #[serde(rename = "compile_fail")]
+    CompileFail,
+    // Indicates that the exercise should be compiled as a binary and formatted like rustfmt does
+    Fmt,
+    // Indicates that the exercise should be tested, and its benchmark run within `max_duration`
+    Perf,
+}
+
+// The hint of an exercise, either a single text or a list of levels
+// that are revealed one at a time, each giving away a bit more
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum Hint {
+    Single(String),
+    Levels(Vec<String>),
+}
+
+impl Default for Hint {
+    fn default() -> Self {
+        Hint::Single(String::new())
+    }
+}
+
+impl Hint {
+    pub fn levels(&self) -> &[String] {
+        match self {
+            Hint::Single(hint) => slice::from_ref(hint),
+            Hint::Levels(levels) => levels,
+        }
+    }
+}
+
+impl Display for Hint {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.levels().join("\n\n"))
+    }
 }
 
 #[derive(Deserialize)]
 pub struct ExerciseList {
+    // The track the exercises of this file belong to
+    #[serde(default)]
+    pub track: Option<String>,
+    // Glob patterns of further manifests, relative to the rustlings directory
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
     pub exercises: Vec<Exercise>,
 }
 
 // A representation of a rustlings exercise.
 // This is deserialized from the accompanying info.toml file
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Clone, Debug, Default)]
 pub struct Exercise {
     // Name of the exercise
     pub name: String,
     // The path to the file containing the exercise's source code
     pub path: PathBuf,
-    // The mode of the exercise (Test, Compile, or Clippy)
+    // The mode of the exercise, which decides how it is checked
     pub mode: Mode,
     // The hint text associated with the exercise
-    pub hint: String,
+    pub hint: Hint,
+    // How many seconds the exercise may run before it is killed
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    // How many MiB of memory the exercise may use
+    #[serde(default)]
+    pub memory_limit: Option<u64>,
+    // How many KiB of output the exercise may produce
+    #[serde(default)]
+    pub output_limit: Option<usize>,
+    // The path to a reference solution, shown once the exercise is verified
+    #[serde(default)]
+    pub solution: Option<PathBuf>,
+    // What the exercise reads from stdin when it runs
+    #[serde(default)]
+    pub stdin: Option<String>,
+    // The command line arguments of a compile or clippy mode exercise
+    #[serde(default)]
+    pub args: Vec<String>,
+    // Environment variables the exercise runs with
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    // The errors a compile_fail mode exercise has to be rejected with, like `E0382`
+    #[serde(default)]
+    pub error_codes: Vec<String>,
+    // What a compile mode exercise has to print
+    #[serde(default)]
+    pub expected_stdout: Option<ExpectedStdout>,
+    // The code a compile mode exercise has to exit with, 0 if not given
+    #[serde(default)]
+    pub expected_exit_code: Option<i32>,
+    // How many milliseconds the benchmark of a perf mode exercise may take
+    #[serde(default)]
+    pub max_duration: Option<u64>,
+    // Whether the exercise has to be formatted like rustfmt does, whatever its mode
+    #[serde(default)]
+    pub also_fmt: bool,
+    // The names of the exercises that have to be verified before this one is offered
+    #[serde(default)]
+    pub requires: Vec<String>,
+    // The track of the manifest the exercise is defined in
+    #[serde(skip)]
+    pub track: String,
 }
 
 // An enum to track of the state of an Exercise.
@@ -82,151 +249,420 @@ pub struct ContextLine {
 // The result of compiling an exercise
 pub struct CompiledExercise<'a> {
     exercise: &'a Exercise,
-    _handle: FileHandle,
+    scratch: ScratchDir,
+    // The output of the compiler
+    pub output: ExerciseOutput,
 }
 
 impl<'a> CompiledExercise<'a> {
     // Run the compiled exercise
-    pub fn run(&self) -> Result<ExerciseOutput, ExerciseOutput> {
-        self.exercise.run()
+    pub fn run(&self) -> Result<ExerciseOutput, RustlingsError> {
+        match self.exercise.mode {
+            // The tests already ran as part of `cargo test` while compiling
+            Mode::BuildScript => Ok(self.output.clone()),
+            // There is nothing to run, the compiler rejected the exercise
+            Mode::CompileFail => Ok(self.output.clone()),
+            Mode::Fmt => Ok(self.output.clone()),
+            Mode::Perf => {
+                self.exercise.run(&self.scratch, &[])?;
+                self.exercise.benchmark(&self.scratch)
+            }
+            _ => self.exercise.run(&self.scratch, &[]),
+        }
+    }
+
+    // Run only the tests of the compiled test harness whose names contain the filter
+    pub fn run_tests(&self, filter: &str) -> Result<ExerciseOutput, RustlingsError> {
+        self.exercise.run(&self.scratch, &[filter])
     }
 }
 
 // A representation of an already executed binary
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ExerciseOutput {
     // The textual contents of the standard output of the binary
     pub stdout: String,
     // The textual contents of the standard error of the binary
     pub stderr: String,
+    // The exit code of the binary, if it wasn't terminated by a signal
+    pub status: Option<i32>,
+    // The limit the binary was stopped for, if any
+    #[serde(skip)]
+    pub limit_exceeded: Option<LimitExceeded>,
+    // The messages of the compiler, which are rendered into `stderr`
+    #[serde(default)]
+    pub diagnostics: Vec<Diagnostic>,
 }
 
-struct FileHandle;
+impl ExerciseOutput {
+    pub fn from_output(output: &process::Output) -> ExerciseOutput {
+        ExerciseOutput {
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            status: output.status.code(),
+            limit_exceeded: None,
+            diagnostics: Vec::new(),
+        }
+    }
 
-impl Drop for FileHandle {
-    fn drop(&mut self) {
-        clean();
+    // Replace the JSON diagnostics rustc printed with their rendering
+    fn read_rustc_diagnostics(&mut self) {
+        let (diagnostics, rest) = diagnostics::parse_rustc(&self.stderr);
+        self.stderr = diagnostics::render(&diagnostics) + &rest;
+        self.diagnostics = diagnostics;
     }
+
+    // Move the diagnostics of the compiler cargo printed as JSON
+    // to the rendering in front of the rest of its errors
+    fn read_cargo_diagnostics(&mut self) {
+        let (diagnostics, rest) = diagnostics::parse_cargo(&self.stdout);
+        self.stdout = rest;
+        self.stderr = diagnostics::render(&diagnostics) + &self.stderr;
+        self.diagnostics = diagnostics;
+    }
+}
+
+// Run a command of the toolchain to completion
+fn tool_output(tool: &str, cmd: &mut Command) -> Result<process::Output, RustlingsError> {
+    cmd.output().map_err(|e| RustlingsError::spawn(tool, e))
 }
 
 impl Exercise {
-    pub fn compile(&self) -> Result<CompiledExercise, ExerciseOutput> {
+    // Compile the exercise, then check that it is formatted if it has to be
+    pub fn compile(&self) -> Result<CompiledExercise<'_>, RustlingsError> {
+        let compiled = self.build()?;
+        if self.mode == Mode::Fmt || self.also_fmt {
+            rustfmt::check(self)?;
+        }
+        Ok(compiled)
+    }
+
+    fn build(&self) -> Result<CompiledExercise<'_>, RustlingsError> {
+        let scratch = ScratchDir::new()
+            .map_err(|e| RustlingsError::io("Failed to create a scratch directory", e))?;
+        let binary = scratch.binary();
         let cmd = match self.mode {
-            Mode::Compile => Command::new("rustc")
-                .args(&[self.path.to_str().unwrap(), "-o", &temp_file()])
-                .args(RUSTC_COLOR_ARGS)
-                .args(RUSTC_EDITION_ARGS)
-                .output(),
-            Mode::Test => Command::new("rustc")
-                .args(&["--test", self.path.to_str().unwrap(), "-o", &temp_file()])
-                .args(RUSTC_COLOR_ARGS)
-                .args(RUSTC_EDITION_ARGS)
-                .output(),
+            Mode::Compile | Mode::CompileFail | Mode::Fmt => tool_output(
+                "rustc",
+                Command::new("rustc")
+                    .arg(&self.path)
+                    .arg("-o")
+                    .arg(&binary)
+                    .args(RUSTC_JSON_ARGS)
+                    .args(RUSTC_EDITION_ARGS),
+            )?,
+            Mode::Doctest => tool_output(
+                "rustc",
+                Command::new("rustc")
+                    .args(["--crate-type", "lib", "--crate-name", &self.crate_name()])
+                    .arg(&self.path)
+                    .arg("-o")
+                    .arg(scratch.library(&self.crate_name()))
+                    .args(RUSTC_JSON_ARGS)
+                    .args(RUSTC_EDITION_ARGS),
+            )?,
+            Mode::Test | Mode::Perf => {
+                let mut rustc = Command::new("rustc");
+                rustc
+                    .arg("--test")
+                    .arg(&self.path)
+                    .arg("-o")
+                    .arg(&binary)
+                    .args(RUSTC_JSON_ARGS)
+                    .args(RUSTC_EDITION_ARGS);
+                if self.mode == Mode::Perf {
+                    rustc.args(OPTIMIZE_ARGS);
+                }
+                tool_output("rustc", &mut rustc)?
+            }
             Mode::Clippy => {
-                let cargo_toml = format!(
-                    r#"[package]
-name = "{}"
-version = "0.0.1"
-edition = "2021"
-[[bin]]
-name = "{}"
-path = "{}.rs""#,
-                    self.name, self.name, self.name
-                );
                 let cargo_toml_error_msg = if env::var("NO_EMOJI").is_ok() {
-                    "Failed to write Clippy Cargo.toml file."
+                    "Failed to write Clippy Cargo.toml file"
                 } else {
-                    "Failed to write 📎 Clippy 📎 Cargo.toml file."
+                    "Failed to write 📎 Clippy 📎 Cargo.toml file"
                 };
-                fs::write(CLIPPY_CARGO_TOML_PATH, cargo_toml).expect(cargo_toml_error_msg);
+                let manifest = scratch
+                    .write_manifest(self, None)
+                    .map_err(|e| RustlingsError::io(cargo_toml_error_msg, e))?;
                 // To support the ability to run the clippy exercises, build
                 // an executable, in addition to running clippy. With a
                 // compilation failure, this would silently fail. But we expect
                 // clippy to reflect the same failure while compiling later.
-                Command::new("rustc")
-                    .args(&[self.path.to_str().unwrap(), "-o", &temp_file()])
-                    .args(RUSTC_COLOR_ARGS)
-                    .args(RUSTC_EDITION_ARGS)
-                    .output()
-                    .expect("Failed to compile!");
-                // Due to an issue with Clippy, a cargo clean is required to catch all lints.
+                tool_output(
+                    "rustc",
+                    Command::new("rustc")
+                        .arg(&self.path)
+                        .arg("-o")
+                        .arg(&binary)
+                        .args(RUSTC_JSON_ARGS)
+                        .args(RUSTC_EDITION_ARGS),
+                )?;
+                // The scratch directory starts out without a target directory,
+                // so clippy always lints from a clean state and catches all lints.
                 // See https://github.com/rust-lang/rust-clippy/issues/2604
-                // This is already fixed on Clippy's master branch. See this issue to track merging into Cargo:
-                // https://github.com/rust-lang/rust-clippy/issues/3837
-                Command::new("cargo")
-                    .args(&["clean", "--manifest-path", CLIPPY_CARGO_TOML_PATH])
-                    .args(RUSTC_COLOR_ARGS)
-                    .output()
-                    .expect("Failed to run 'cargo clean'");
-                Command::new("cargo")
-                    .args(&["clippy", "--manifest-path", CLIPPY_CARGO_TOML_PATH])
-                    .args(RUSTC_COLOR_ARGS)
-                    .args(&["--", "-D", "warnings", "-D", "clippy::float_cmp"])
-                    .output()
-            },
+                tool_output(
+                    "cargo",
+                    Command::new("cargo")
+                        .arg("clippy")
+                        .arg("--manifest-path")
+                        .arg(&manifest)
+                        .args(CARGO_JSON_ARGS)
+                        .args(CLIPPY_ARGS),
+                )?
+            }
             Mode::BuildScript => {
-                let cargo_toml = format!(
-                    r#"[package]
-name = "{}"
-version = "0.0.1"
-edition = "2021"
-[[bin]]
-name = "{}"
-path = "{}.rs""#,
-                    self.name, self.name, self.name
-                );
-                let cargo_toml_error_msg = if env::var("NO_EMOJI").is_ok() {
-                    "Failed to write Clippy Cargo.toml file."
-                } else {
-                    "Failed to write 📎 Clippy 📎 Cargo.toml file."
+                // The build script shared by the exercises lives next to them
+                let build_script = self.path.with_file_name("build.rs");
+                let manifest = scratch
+                    .write_manifest(self, Some(&build_script))
+                    .map_err(|e| RustlingsError::io("Failed to write Cargo.toml file", e))?;
+
+                // This runs the tests as well, so it is limited like a binary would be.
+                // Cargo and rustc need more memory than exercises, so that isn't capped.
+                let limits = Limits {
+                    memory: None,
+                    ..self.limits()
                 };
-                fs::write(BUILD_SCRIPT_CARGO_TOML_PATH, cargo_toml).expect(cargo_toml_error_msg);
-
-                Command::new("cargo")
-                    .args(&["test", "--manifest-path", BUILD_SCRIPT_CARGO_TOML_PATH])
-                    .output()
+                let (cmd, limit_exceeded) = limits::output_with_limits(
+                    Command::new("cargo")
+                        .arg("test")
+                        .arg("--manifest-path")
+                        .arg(&manifest)
+                        .envs(&self.env),
+                    self.stdin.as_deref(),
+                    &limits,
+                )
+                .map_err(|e| RustlingsError::spawn("cargo", e))?;
+                let mut output = ExerciseOutput::from_output(&cmd);
+                output.limit_exceeded = limit_exceeded;
+                if !cmd.status.success() || limit_exceeded.is_some() {
+                    // Cargo reports failing tests like this, anything else is a build failure
+                    let tests_ran = output.stderr.contains("error: test failed");
+                    return Err(if tests_ran || limit_exceeded.is_some() {
+                        self.run_failure(output)
+                    } else {
+                        RustlingsError::CompileFailure {
+                            exercise: self.name.clone(),
+                            output,
+                        }
+                    });
+                }
+                return Ok(CompiledExercise {
+                    exercise: self,
+                    scratch,
+                    output,
+                });
             }
-        }
-        .expect("Failed to run 'compile' command.");
+        };
 
+        let mut output = ExerciseOutput::from_output(&cmd);
+        match self.mode {
+            Mode::Clippy => output.read_cargo_diagnostics(),
+            _ => output.read_rustc_diagnostics(),
+        }
+        if self.mode == Mode::CompileFail {
+            return self.expect_rejection(cmd.status.success(), output, scratch);
+        }
         if cmd.status.success() {
             Ok(CompiledExercise {
                 exercise: self,
-                _handle: FileHandle,
+                scratch,
+                output,
             })
         } else {
-            clean();
-            Err(ExerciseOutput {
-                stdout: String::from_utf8_lossy(&cmd.stdout).to_string(),
-                stderr: String::from_utf8_lossy(&cmd.stderr).to_string(),
+            Err(RustlingsError::CompileFailure {
+                exercise: self.name.clone(),
+                output,
+            })
+        }
+    }
+
+    // A compile_fail exercise passes when all errors it is rejected with
+    // have one of its error codes
+    fn expect_rejection(
+        &self,
+        compiled: bool,
+        output: ExerciseOutput,
+        scratch: ScratchDir,
+    ) -> Result<CompiledExercise<'_>, RustlingsError> {
+        let exercise = self.name.clone();
+        let codes = diagnostics::error_codes(&output.diagnostics);
+        if compiled {
+            Err(RustlingsError::CompiledUnexpectedly { exercise, output })
+        } else if !codes.is_empty()
+            && codes
+                .iter()
+                .all(|code| self.error_codes.iter().any(|c| c == code))
+        {
+            Ok(CompiledExercise {
+                exercise: self,
+                scratch,
+                output,
             })
+        } else {
+            Err(RustlingsError::CompileFailure { exercise, output })
         }
     }
 
-    fn run(&self) -> Result<ExerciseOutput, ExerciseOutput> {
-        let arg = match self.mode {
-            Mode::Test => "--show-output",
-            Mode::BuildScript => return Ok(ExerciseOutput {
-                stdout: "".to_string(),
-                stderr: "".to_string(),
-            }),
-            _ => "",
+    // The error codes a compile_fail exercise has to be rejected with, for messages
+    pub fn expected_errors(&self) -> String {
+        self.error_codes.join(" or ")
+    }
+
+    // The name of the crate of the exercise, which its doc examples use
+    fn crate_name(&self) -> String {
+        let stem = self.path.file_stem().unwrap_or_default().to_string_lossy();
+        stem.replace('-', "_")
+    }
+
+    fn run(
+        &self,
+        scratch: &ScratchDir,
+        test_args: &[&str],
+    ) -> Result<ExerciseOutput, RustlingsError> {
+        let mut limits = self.limits();
+        let mut command = match self.mode {
+            Mode::Doctest => {
+                // rustdoc compiles the examples first, like with build scripts
+                // the memory of the compiler isn't capped
+                limits.memory = None;
+                let mut command = Command::new("rustdoc");
+                command
+                    .arg("--test")
+                    .arg(&self.path)
+                    .args(["--crate-name", &self.crate_name()])
+                    .arg("-L")
+                    .arg(&scratch.path)
+                    .args(RUSTC_EDITION_ARGS);
+                if !test_args.is_empty() {
+                    command.arg("--test-args").arg(test_args.join(" "));
+                }
+                command
+            }
+            Mode::Test | Mode::Perf => {
+                let mut command = Command::new(scratch.binary());
+                command.arg("--show-output").args(test_args);
+                command
+            }
+            _ => {
+                let mut command = Command::new(scratch.binary());
+                command.args(&self.args);
+                command
+            }
         };
-        println!("pa={}", temp_file());
-        let cmd = Command::new(&temp_file())
-            .arg(arg)
-            .output()
-            .expect("Failed to run 'run' command");
-
-        let output = ExerciseOutput {
-            stdout: String::from_utf8_lossy(&cmd.stdout).to_string(),
-            stderr: String::from_utf8_lossy(&cmd.stderr).to_string(),
+        command.envs(&self.env);
+        let (cmd, limit_exceeded) =
+            limits::output_with_limits(&mut command, self.stdin.as_deref(), &limits).map_err(
+                |e| match self.mode {
+                    Mode::Doctest => RustlingsError::spawn("rustdoc", e),
+                    _ => RustlingsError::io(format!("Failed to run {self}"), e),
+                },
+            )?;
+
+        let mut output = ExerciseOutput::from_output(&cmd);
+        output.limit_exceeded = limit_exceeded;
+        if limit_exceeded.is_none() && self.mismatch(&output).is_some() {
+            return Err(RustlingsError::WrongOutput {
+                exercise: self.name.clone(),
+                output,
+            });
+        }
+        let exit_code = match self.mode {
+            Mode::Compile => self.expected_exit_code.unwrap_or(0),
+            _ => 0,
         };
+        if output.status == Some(exit_code) && limit_exceeded.is_none() {
+            Ok(output)
+        } else {
+            Err(self.run_failure(output))
+        }
+    }
 
-        if cmd.status.success() {
+    // Classify why running the code of the exercise failed
+    fn run_failure(&self, output: ExerciseOutput) -> RustlingsError {
+        let exercise = self.name.clone();
+        match output.limit_exceeded {
+            Some(LimitExceeded::Timeout(_)) => RustlingsError::Timeout { exercise, output },
+            Some(_) => RustlingsError::LimitExceeded { exercise, output },
+            None if matches!(
+                self.mode,
+                Mode::Test | Mode::BuildScript | Mode::Doctest | Mode::Perf
+            ) =>
+            {
+                RustlingsError::TestFailure { exercise, output }
+            }
+            None => RustlingsError::RuntimeFailure { exercise, output },
+        }
+    }
+
+    // Run the tests of a perf mode exercise marked with `#[ignore]`, which are
+    // its benchmark, a couple of times. The output of the last run ends with
+    // how long the median run took, which has to be within the `max_duration`
+    // of the exercise. The time is the one the test harness reports, so
+    // starting the process doesn't count.
+    // Once most runs took too long, the median can't be within it anymore.
+    fn benchmark(&self, scratch: &ScratchDir) -> Result<ExerciseOutput, RustlingsError> {
+        let budget = Duration::from_millis(self.max_duration.unwrap_or_default());
+        let mut durations = Vec::with_capacity(BENCHMARK_RUNS);
+        let mut output = None;
+        while durations.len() < BENCHMARK_RUNS
+            && durations.iter().filter(|&&d| d > budget).count() <= BENCHMARK_RUNS / 2
+        {
+            let start = Instant::now();
+            let run = self.run(scratch, &["--ignored"])?;
+            durations.push(libtest::duration(&run.stdout).unwrap_or_else(|| start.elapsed()));
+            if libtest::parse(&run.stdout).is_empty() {
+                return Err(RustlingsError::Manifest {
+                    message: format!(
+                        "{} has no benchmark, its tests marked with `#[ignore]`",
+                        self.name
+                    ),
+                });
+            }
+            output = Some(run);
+        }
+        durations.sort();
+        let median = durations[durations.len() / 2];
+        let runs = durations.len();
+        let mut output = output.expect("The benchmark runs at least once");
+        // The harness reports the time in hundredths of a second
+        output.stdout += &format!(
+            "The benchmark took {:.2}s in the median of {runs} runs, the budget is {budget:?}\n",
+            median.as_secs_f64()
+        );
+        if median <= budget {
             Ok(output)
         } else {
-            Err(output)
+            Err(RustlingsError::TooSlow {
+                exercise: self.name.clone(),
+                output,
+            })
+        }
+    }
+
+    // How the run of the exercise differs from what info.toml expects of it,
+    // only compile mode exercises have expectations
+    pub fn mismatch(&self, output: &ExerciseOutput) -> Option<Mismatch> {
+        match self.mode {
+            Mode::Compile => expected::check(
+                self.expected_stdout.as_ref(),
+                self.expected_exit_code,
+                output,
+            ),
+            _ => None,
+        }
+    }
+
+    // The limits the exercise runs with, the ones from info.toml or the defaults
+    pub fn limits(&self) -> Limits {
+        let defaults = Limits::default();
+        Limits {
+            timeout: self.timeout.map_or(defaults.timeout, Duration::from_secs),
+            memory: self
+                .memory_limit
+                .map(|mib| mib * 1024 * 1024)
+                .or(defaults.memory),
+            output: self.output_limit.map_or(defaults.output, |kib| kib * 1024),
         }
     }
 
@@ -270,15 +706,22 @@ path = "{}.rs""#,
 
         State::Pending(context)
     }
+}
 
-    // Check that the exercise looks to be solved using self.state()
-    // This is not the best way to check since
-    // the user can just remove the "I AM NOT DONE" string from the file
-    // without actually having solved anything.
-    // The only other way to truly check this would to compile and run
-    // the exercise; which would be both costly and counterintuitive
-    pub fn looks_done(&self) -> bool {
-        self.state() == State::Done
+impl Display for Mode {
+    // The name of the mode, as written in info.toml
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let name = match self {
+            Mode::Compile => "compile",
+            Mode::Test => "test",
+            Mode::Clippy => "clippy",
+            Mode::BuildScript => "buildscript",
+            Mode::Doctest => "doctest",
+            Mode::CompileFail => "compile_fail",
+            Mode::Fmt => "fmt",
+            Mode::Perf => "perf",
+        };
+        write!(f, "{name}")
     }
 }
 
@@ -288,28 +731,38 @@ impl Display for Exercise {
     }
 }
 
-#[inline]
-fn clean() {
-    let _ignored = remove_file(&temp_file());
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
-    use std::path::Path;
 
     #[test]
     fn test_clean() {
-        File::create(&temp_file()).unwrap();
         let exercise = Exercise {
             name: String::from("example"),
             path: PathBuf::from("tests/fixture/state/pending_exercise.rs"),
             mode: Mode::Compile,
-            hint: String::from(""),
+            hint: Hint::default(),
+            ..Default::default()
         };
         let compiled = exercise.compile().unwrap();
+        let scratch = compiled.scratch.path.clone();
+        assert!(scratch.exists());
         drop(compiled);
-        assert!(!Path::new(&temp_file()).exists());
+        assert!(!scratch.exists());
+    }
+
+    #[test]
+    fn test_compilations_do_not_share_binaries() {
+        let exercise = Exercise {
+            name: String::from("example"),
+            path: PathBuf::from("tests/fixture/state/pending_exercise.rs"),
+            mode: Mode::Compile,
+            hint: Hint::default(),
+            ..Default::default()
+        };
+        let first = exercise.compile().unwrap();
+        let second = exercise.compile().unwrap();
+        assert_ne!(first.scratch.binary(), second.scratch.binary());
     }
 
     #[test]
@@ -318,7 +771,8 @@ mod test {
             name: "pending_exercise".into(),
             path: PathBuf::from("tests/fixture/state/pending_exercise.rs"),
             mode: Mode::Compile,
-            hint: String::new(),
+            hint: Hint::default(),
+            ..Default::default()
         };
 
         let state = exercise.state();
@@ -359,7 +813,8 @@ mod test {
             name: "finished_exercise".into(),
             path: PathBuf::from("tests/fixture/state/finished_exercise.rs"),
             mode: Mode::Compile,
-            hint: String::new(),
+            hint: Hint::default(),
+            ..Default::default()
         };
 
         assert_eq!(exercise.state(), State::Done);
@@ -371,7 +826,8 @@ mod test {
             name: "exercise_with_output".into(),
             path: PathBuf::from("tests/fixture/success/testSuccess.rs"),
             mode: Mode::Test,
-            hint: String::new(),
+            hint: Hint::default(),
+            ..Default::default()
         };
         let out = exercise.compile().unwrap().run().unwrap();
         assert!(out.stdout.contains("THIS TEST TOO SHALL PASS"));
@@ -0,0 +1,145 @@
+use crate::diff;
+use crate::exercise::ExerciseOutput;
+use regex::Regex;
+use serde::Deserialize;
+use std::fmt::{self, Display, Formatter};
+
+// What a compile mode exercise has to print, `expected_stdout` in info.toml
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum ExpectedStdout {
+    // The exact output, trailing whitespace aside
+    Exact(String),
+    // A regex the output without trailing whitespace has to match,
+    // written as `{ regex = "..." }`
+    Regex { regex: String },
+}
+
+impl ExpectedStdout {
+    pub fn matches(&self, stdout: &str) -> bool {
+        match self {
+            ExpectedStdout::Exact(expected) => stdout.trim_end() == expected.trim_end(),
+            // The manifest check rejects invalid regexes
+            ExpectedStdout::Regex { regex } => {
+                Regex::new(regex).is_ok_and(|regex| regex.is_match(stdout.trim_end()))
+            }
+        }
+    }
+}
+
+// How the run of an exercise differs from what info.toml expects
+#[derive(Clone, Debug, PartialEq)]
+pub enum Mismatch {
+    ExitCode {
+        expected: i32,
+        // `None` if it was killed by a signal
+        actual: Option<i32>,
+    },
+    Stdout {
+        expected: ExpectedStdout,
+        actual: String,
+    },
+}
+
+impl Mismatch {
+    // The lines of the expected output that are missing and the ones that
+    // were printed instead, if the output has to be exactly the expected one
+    pub fn diff(&self) -> Option<String> {
+        match self {
+            Mismatch::Stdout {
+                expected: ExpectedStdout::Exact(expected),
+                actual,
+            } => Some(diff::unified(
+                "expected output",
+                "actual output",
+                expected,
+                actual,
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Mismatch::ExitCode {
+                expected,
+                actual: Some(actual),
+            } => write!(f, "exited with code {actual} instead of {expected}"),
+            Mismatch::ExitCode { expected, .. } => {
+                write!(f, "was killed instead of exiting with code {expected}")
+            }
+            Mismatch::Stdout {
+                expected: ExpectedStdout::Exact(_),
+                ..
+            } => write!(f, "didn't print the expected output"),
+            Mismatch::Stdout {
+                expected: ExpectedStdout::Regex { regex },
+                ..
+            } => write!(f, "printed output that doesn't match `{regex}`"),
+        }
+    }
+}
+
+// Compare the run of an exercise with what it is expected to do. Exiting
+// with another code than 0 is only a mismatch if a code is expected, without
+// one it is a plain runtime failure and the output isn't compared.
+pub fn check(
+    expected_stdout: Option<&ExpectedStdout>,
+    expected_exit_code: Option<i32>,
+    output: &ExerciseOutput,
+) -> Option<Mismatch> {
+    if output.status != Some(expected_exit_code.unwrap_or(0)) {
+        return expected_exit_code.map(|expected| Mismatch::ExitCode {
+            expected,
+            actual: output.status,
+        });
+    }
+    expected_stdout
+        .filter(|expected| !expected.matches(&output.stdout))
+        .map(|expected| Mismatch::Stdout {
+            expected: expected.clone(),
+            actual: output.stdout.clone(),
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn output(stdout: &str, status: i32) -> ExerciseOutput {
+        ExerciseOutput {
+            stdout: stdout.into(),
+            stderr: String::new(),
+            status: Some(status),
+            limit_exceeded: None,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_check_exit_code_then_stdout() {
+        let exact = ExpectedStdout::Exact("Hello\n".into());
+        let regex = ExpectedStdout::Regex {
+            regex: r"^\d+ apples$".into(),
+        };
+        assert_eq!(check(Some(&exact), None, &output("Hello", 0)), None);
+        assert_eq!(check(Some(&regex), None, &output("3 apples", 0)), None);
+        assert_eq!(check(Some(&exact), None, &output("Hello", 1)), None);
+        assert_eq!(
+            check(None, Some(2), &output("", 1)),
+            Some(Mismatch::ExitCode {
+                expected: 2,
+                actual: Some(1)
+            })
+        );
+        assert_eq!(
+            check(Some(&regex), Some(1), &output("apples", 1)),
+            Some(Mismatch::Stdout {
+                expected: regex.clone(),
+                actual: "apples".into()
+            })
+        );
+    }
+}
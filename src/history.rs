@@ -0,0 +1,168 @@
+use crate::diagnostics;
+use crate::error::RustlingsError;
+use crate::exercise::Exercise;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HISTORY_DIR: &str = ".rustlings/history";
+// The oldest snapshots are dropped once an exercise has more than this
+const MAX_SNAPSHOTS: usize = 100;
+
+// How checking a snapshot of an exercise went
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Passed,
+    // It works, but still has its `I AM NOT DONE` comment
+    Pending,
+    Failed,
+    // It was saved before being overwritten by `restore`, without being checked
+    Unchecked,
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let outcome = match self {
+            Outcome::Passed => "passed",
+            Outcome::Pending => "pending",
+            Outcome::Failed => "failed",
+            Outcome::Unchecked => "unchecked",
+        };
+        f.pad(outcome)
+    }
+}
+
+// A version of an exercise saved while watching
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Snapshot {
+    // Seconds since the Unix epoch at which it was checked
+    pub saved_at: u64,
+    pub source: String,
+    pub outcome: Outcome,
+    // The code of the first compiler error, like `E0308`
+    pub error_code: Option<String>,
+}
+
+impl Snapshot {
+    fn new(source: String, result: Result<(), &RustlingsError>) -> Snapshot {
+        let (outcome, error_code) = match result {
+            Ok(()) => (Outcome::Passed, None),
+            Err(RustlingsError::Pending { .. }) => (Outcome::Pending, None),
+            Err(err) => (
+                Outcome::Failed,
+                err.output()
+                    .and_then(|output| diagnostics::first_error_code(&output.diagnostics))
+                    .map(str::to_string),
+            ),
+        };
+        Snapshot {
+            saved_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            source,
+            outcome,
+            error_code,
+        }
+    }
+}
+
+fn path(exercise: &Exercise) -> PathBuf {
+    PathBuf::from(HISTORY_DIR).join(format!("{}.json", exercise.name))
+}
+
+// The snapshots of the exercise, oldest first
+pub fn load(exercise: &Exercise) -> Vec<Snapshot> {
+    fs::read_to_string(path(exercise))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save(exercise: &Exercise, snapshots: &[Snapshot]) -> Result<(), RustlingsError> {
+    let path = path(exercise);
+    let serialized = serde_json::to_string(snapshots).expect("Failed to serialize the history");
+    fs::create_dir_all(HISTORY_DIR)
+        .and_then(|_| fs::write(&path, serialized))
+        .map_err(|e| RustlingsError::io(format!("Failed to write {}", path.display()), e))
+}
+
+// Add the current source of the exercise and how checking it went to its
+// history, unless that is what the last snapshot already says
+pub fn record(
+    exercise: &Exercise,
+    result: Result<(), &RustlingsError>,
+) -> Result<(), RustlingsError> {
+    let source = fs::read_to_string(&exercise.path).map_err(|e| {
+        RustlingsError::io(format!("Failed to read {}", exercise.path.display()), e)
+    })?;
+    let snapshot = Snapshot::new(source, result);
+    let mut snapshots = load(exercise);
+    if let Some(last) = snapshots.last() {
+        if last.source == snapshot.source && last.outcome == snapshot.outcome {
+            return Ok(());
+        }
+    }
+    snapshots.push(snapshot);
+    let excess = snapshots.len().saturating_sub(MAX_SNAPSHOTS);
+    snapshots.drain(..excess);
+    save(exercise, &snapshots)
+}
+
+// Overwrite the exercise with its `number`th snapshot, counting from 1,
+// returning whether there is one. Code that isn't in the history yet is
+// added to it first, so it isn't lost.
+pub fn restore(exercise: &Exercise, number: usize) -> Result<bool, RustlingsError> {
+    let mut snapshots = load(exercise);
+    let source = match number.checked_sub(1).and_then(|i| snapshots.get(i)) {
+        Some(snapshot) => snapshot.source.clone(),
+        None => return Ok(false),
+    };
+    let current = fs::read_to_string(&exercise.path).unwrap_or_default();
+    if !snapshots.iter().any(|snapshot| snapshot.source == current) {
+        let mut snapshot = Snapshot::new(current, Ok(()));
+        snapshot.outcome = Outcome::Unchecked;
+        snapshots.push(snapshot);
+        save(exercise, &snapshots)?;
+    }
+    fs::write(&exercise.path, source).map_err(|e| {
+        RustlingsError::io(format!("Failed to write {}", exercise.path.display()), e)
+    })?;
+    Ok(true)
+}
+
+// How long ago a point in time was, like `5 minutes ago`
+pub fn ago(saved_at: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let seconds = now.saturating_sub(saved_at);
+    let (count, unit) = match seconds {
+        0..=59 => return "just now".to_string(),
+        60..=3599 => (seconds / 60, "minute"),
+        3600..=86399 => (seconds / 3600, "hour"),
+        _ => (seconds / 86400, "day"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{count} {unit}{plural} ago")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ago() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        assert_eq!(ago(now), "just now");
+        assert_eq!(ago(now - 60), "1 minute ago");
+        assert_eq!(ago(now - 3 * 3600), "3 hours ago");
+    }
+}
@@ -0,0 +1,257 @@
+use console::style;
+use regex::Regex;
+use std::fmt::Write as _;
+use std::time::Duration;
+
+// How a single test of a test harness went
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Status {
+    Passed,
+    Failed,
+    Ignored,
+}
+
+// The result of a single test, as reported by libtest
+#[derive(PartialEq, Debug)]
+pub struct TestResult {
+    pub name: String,
+    pub status: Status,
+    // What the test panicked with, for failed tests
+    pub message: Option<String>,
+}
+
+// Read the results of the tests from the output of a test harness
+pub fn parse(stdout: &str) -> Vec<TestResult> {
+    let line_re = Regex::new(r"^test (.+?) \.\.\. (ok|FAILED|ignored)").unwrap();
+    let mut results: Vec<TestResult> = stdout
+        .lines()
+        .filter_map(|line| line_re.captures(line))
+        .map(|captures| TestResult {
+            name: captures[1].to_string(),
+            status: match &captures[2] {
+                "ok" => Status::Passed,
+                "FAILED" => Status::Failed,
+                _ => Status::Ignored,
+            },
+            message: None,
+        })
+        .collect();
+
+    // The output of the failed tests follows the first `failures:` line,
+    // each under a `---- <name> stdout ----` header
+    let failures = stdout
+        .lines()
+        .skip_while(|line| *line != "failures:")
+        .skip(1)
+        .take_while(|line| *line != "failures:");
+    let mut section: Option<(&str, Vec<&str>)> = None;
+    let mut sections = Vec::new();
+    for line in failures {
+        let header = line
+            .strip_prefix("---- ")
+            .and_then(|rest| rest.strip_suffix(" stdout ----"));
+        match header {
+            Some(name) => sections.extend(section.replace((name, Vec::new()))),
+            None => {
+                if let Some((_, lines)) = &mut section {
+                    lines.push(line);
+                }
+            }
+        }
+    }
+    sections.extend(section);
+
+    for (name, lines) in sections {
+        if let Some(result) = results.iter_mut().find(|result| result.name == name) {
+            result.message = Some(panic_message(&lines));
+        }
+    }
+    results
+}
+
+// How long the tests took as measured by the harness itself, which leaves
+// out starting the process. Only given with the summary of the run.
+pub fn duration(stdout: &str) -> Option<Duration> {
+    let summary_re = Regex::new(r"^test result: .* finished in (\d+(?:\.\d+)?)s$").unwrap();
+    stdout
+        .lines()
+        .find_map(|line| summary_re.captures(line))
+        .and_then(|captures| captures[1].parse().ok())
+        .map(Duration::from_secs_f64)
+}
+
+// The message a test panicked with, without what it printed before or the backtrace
+fn panic_message(lines: &[&str]) -> String {
+    let start = lines
+        .iter()
+        .position(|line| line.starts_with("thread '") && line.contains(" panicked at "));
+    let message: Vec<&str> = match start {
+        // Since Rust 1.73 the message follows on the next lines
+        Some(start) if lines[start].ends_with(':') => lines[start + 1..]
+            .iter()
+            .copied()
+            .take_while(|line| !line.starts_with("stack backtrace:") && !line.starts_with("note: "))
+            .collect(),
+        Some(start) => lines[start].splitn(2, " panicked at ").skip(1).collect(),
+        // Tests returning an error don't panic, they print it
+        None => lines.to_vec(),
+    };
+    message.join("\n").trim().to_string()
+}
+
+// A compact table of the results, followed by the message of every failed
+// test. The values compared by a failed `assert_eq!` have their
+// differences highlighted. Returns `None` if no tests ran.
+pub fn report(results: &[TestResult]) -> Option<String> {
+    if results.is_empty() {
+        return None;
+    }
+    let mut out = String::new();
+    for result in results {
+        let status = match result.status {
+            Status::Passed => style("ok     ").green(),
+            Status::Failed => style("FAILED ").red(),
+            Status::Ignored => style("ignored").yellow(),
+        };
+        let _ = writeln!(out, "  {status}  {}", result.name);
+    }
+    let count = |status| results.iter().filter(|r| r.status == status).count();
+    let _ = writeln!(
+        out,
+        "{} passed, {} failed, {} ignored",
+        count(Status::Passed),
+        count(Status::Failed),
+        count(Status::Ignored)
+    );
+
+    for result in results {
+        if let Some(message) = &result.message {
+            let _ = writeln!(out, "\n{}", style(format!("{}:", result.name)).bold());
+            let _ = writeln!(out, "{}", highlight_assert_eq(message));
+        }
+    }
+    Some(out)
+}
+
+// Highlight where the `left` and `right` values of a failed
+// `assert_eq!` differ, leaving any other message as it is
+fn highlight_assert_eq(message: &str) -> String {
+    let find = |prefix: &str| message.lines().find_map(|line| line.strip_prefix(prefix));
+    let (left, right) = match (find("  left: "), find(" right: ")) {
+        (Some(left), Some(right)) => (left, right),
+        _ => return message.to_string(),
+    };
+
+    let left_chars: Vec<char> = left.chars().collect();
+    let right_chars: Vec<char> = right.chars().collect();
+    let prefix = left_chars
+        .iter()
+        .zip(&right_chars)
+        .take_while(|(l, r)| l == r)
+        .count();
+    let suffix = left_chars[prefix..]
+        .iter()
+        .rev()
+        .zip(right_chars[prefix..].iter().rev())
+        .take_while(|(l, r)| l == r)
+        .count();
+    let mark = |chars: &[char], colored: fn(String) -> String| {
+        let text = |range: &[char]| range.iter().collect::<String>();
+        format!(
+            "{}{}{}",
+            text(&chars[..prefix]),
+            colored(text(&chars[prefix..chars.len() - suffix])),
+            text(&chars[chars.len() - suffix..])
+        )
+    };
+    let left = mark(&left_chars, |s| style(s).red().underlined().to_string());
+    let right = mark(&right_chars, |s| style(s).green().underlined().to_string());
+
+    message
+        .lines()
+        .map(|line| {
+            if line.starts_with("  left: ") {
+                format!("  left: {left}")
+            } else if line.starts_with(" right: ") {
+                format!(" right: {right}")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const OUTPUT: &str = "
+running 3 tests
+test tests::a ... ok
+test tests::b ... FAILED
+test tests::c ... ignored
+
+successes:
+
+---- tests::a stdout ----
+hi
+
+successes:
+    tests::a
+
+failures:
+
+---- tests::b stdout ----
+debugging output
+
+thread 'tests::b' (20172) panicked at t.rs:4:22:
+assertion `left == right` failed
+  left: [1, 2, 3]
+ right: [1, 5, 3]
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+
+failures:
+    tests::b
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.00s
+";
+
+    #[test]
+    fn test_parse_results_and_panic_messages() {
+        assert_eq!(
+            parse(OUTPUT),
+            [
+                TestResult {
+                    name: "tests::a".into(),
+                    status: Status::Passed,
+                    message: None,
+                },
+                TestResult {
+                    name: "tests::b".into(),
+                    status: Status::Failed,
+                    message: Some(
+                        "assertion `left == right` failed\n  left: [1, 2, 3]\n right: [1, 5, 3]"
+                            .into()
+                    ),
+                },
+                TestResult {
+                    name: "tests::c".into(),
+                    status: Status::Ignored,
+                    message: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duration_of_the_run() {
+        assert_eq!(duration(OUTPUT), Some(Duration::ZERO));
+        assert_eq!(
+            duration("test result: ok. 1 passed; 0 failed; finished in 2.35s\n"),
+            Some(Duration::from_millis(2350))
+        );
+        assert_eq!(duration("test tests::a ... ok\n"), None);
+    }
+}
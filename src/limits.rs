@@ -0,0 +1,263 @@
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Read, Write};
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+// How long an exercise may run when info.toml doesn't say otherwise
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+// How much memory an exercise may use when info.toml doesn't say otherwise
+pub const DEFAULT_MEMORY_LIMIT: u64 = 2048 * 1024 * 1024;
+// How much output an exercise may produce when info.toml doesn't say otherwise
+pub const DEFAULT_OUTPUT_LIMIT: usize = 1024 * 1024;
+
+// How often a running child is checked for having finished
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// The resources a process running exercise code may use
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Limits {
+    // Wall clock time after which the process is killed
+    pub timeout: Duration,
+    // Address space limit in bytes, only enforced on Unix
+    pub memory: Option<u64>,
+    // Combined size of stdout and stderr in bytes
+    pub output: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            timeout: DEFAULT_TIMEOUT,
+            memory: Some(DEFAULT_MEMORY_LIMIT),
+            output: DEFAULT_OUTPUT_LIMIT,
+        }
+    }
+}
+
+// The limit a process was stopped for
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LimitExceeded {
+    Timeout(Duration),
+    Memory(u64),
+    Output(usize),
+}
+
+impl Display for LimitExceeded {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            LimitExceeded::Timeout(timeout) => {
+                write!(f, "timed out after {:.1}s", timeout.as_secs_f64())
+            }
+            LimitExceeded::Memory(bytes) => {
+                write!(f, "memory limit of {} MiB exceeded", bytes / (1024 * 1024))
+            }
+            LimitExceeded::Output(bytes) => {
+                write!(f, "output limit of {} KiB exceeded", bytes / 1024)
+            }
+        }
+    }
+}
+
+// Run the command to completion like `Command::output` does, but kill it
+// as soon as it exceeds one of the limits.
+// The input is written to its stdin, without any its stdin is empty.
+pub fn output_with_limits(
+    cmd: &mut Command,
+    input: Option<&str>,
+    limits: &Limits,
+) -> io::Result<(Output, Option<LimitExceeded>)> {
+    let stdin = if input.is_some() {
+        Stdio::piped()
+    } else {
+        Stdio::null()
+    };
+    cmd.stdin(stdin)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    restrict(cmd, limits);
+    let mut child = cmd.spawn()?;
+
+    // Written on a thread of its own, so a child that doesn't read all of
+    // its input can't block us. Dropping the pipe closes the child's stdin.
+    if let (Some(mut pipe), Some(input)) = (child.stdin.take(), input) {
+        let input = input.to_string();
+        thread::spawn(move || {
+            let _ = pipe.write_all(input.as_bytes());
+        });
+    }
+
+    let captured = Arc::new(AtomicUsize::new(0));
+    let overflowed = Arc::new(AtomicBool::new(false));
+    let stdout = capture(child.stdout.take(), limits.output, &captured, &overflowed);
+    let stderr = capture(child.stderr.take(), limits.output, &captured, &overflowed);
+
+    let deadline = Instant::now() + limits.timeout;
+    let mut exceeded = None;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if overflowed.load(Ordering::SeqCst) {
+            exceeded = Some(LimitExceeded::Output(limits.output));
+        } else if Instant::now() >= deadline {
+            exceeded = Some(LimitExceeded::Timeout(limits.timeout));
+        }
+        if exceeded.is_some() {
+            let _ = child.kill();
+            break child.wait()?;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let output = Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    };
+    // The child may also have died from the pipe we closed on overflow
+    if exceeded.is_none() && overflowed.load(Ordering::SeqCst) {
+        exceeded = Some(LimitExceeded::Output(limits.output));
+    }
+    // Running out of address space makes the allocator abort the process
+    if exceeded.is_none() && !output.status.success() {
+        if let Some(memory) = limits.memory {
+            if String::from_utf8_lossy(&output.stderr).contains("memory allocation of") {
+                exceeded = Some(LimitExceeded::Memory(memory));
+            }
+        }
+    }
+    Ok((output, exceeded))
+}
+
+// Read a pipe on a separate thread, keeping at most `limit` bytes
+// across all pipes sharing the `captured` counter
+fn capture<R: Read + Send + 'static>(
+    pipe: Option<R>,
+    limit: usize,
+    captured: &Arc<AtomicUsize>,
+    overflowed: &Arc<AtomicBool>,
+) -> JoinHandle<Vec<u8>> {
+    let captured = Arc::clone(captured);
+    let overflowed = Arc::clone(overflowed);
+    thread::spawn(move || {
+        let mut kept = Vec::new();
+        let mut pipe = match pipe {
+            Some(pipe) => pipe,
+            None => return kept,
+        };
+        let mut buffer = [0; 8192];
+        while let Ok(n) = pipe.read(&mut buffer) {
+            if n == 0 {
+                break;
+            }
+            let total = captured.fetch_add(n, Ordering::SeqCst) + n;
+            if total > limit {
+                let room = n.saturating_sub(total - limit);
+                kept.extend_from_slice(&buffer[..room]);
+                overflowed.store(true, Ordering::SeqCst);
+                break;
+            }
+            kept.extend_from_slice(&buffer[..n]);
+        }
+        kept
+    })
+}
+
+#[cfg(unix)]
+fn restrict(cmd: &mut Command, limits: &Limits) {
+    use std::os::unix::process::CommandExt;
+
+    // The child stays in our process group, so Ctrl-C in the terminal reaches
+    // it too. Should rustlings die before it could kill the child anyway,
+    // the CPU limit stops it: within the timeout the child can't use more CPU
+    // time than the timeout on every core.
+    let cores = thread::available_parallelism().map_or(1, |n| n.get() as u64);
+    let cpu_seconds = (limits.timeout.as_secs() + 1) * cores;
+    let cpu = libc::rlimit {
+        rlim_cur: cpu_seconds as libc::rlim_t,
+        rlim_max: (cpu_seconds + 1) as libc::rlim_t,
+    };
+    let memory = limits.memory.map(|memory| libc::rlimit {
+        rlim_cur: memory as libc::rlim_t,
+        rlim_max: memory as libc::rlim_t,
+    });
+    // SAFETY: `setrlimit` is async-signal-safe and only touches the child
+    unsafe {
+        cmd.pre_exec(move || {
+            if libc::setrlimit(libc::RLIMIT_CPU, &cpu) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if let Some(memory) = &memory {
+                if libc::setrlimit(libc::RLIMIT_AS, memory) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+fn restrict(_cmd: &mut Command, _limits: &Limits) {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_timeout_kills_the_child() {
+        let limits = Limits {
+            timeout: Duration::from_millis(200),
+            ..Limits::default()
+        };
+        let start = Instant::now();
+        let (output, exceeded) =
+            output_with_limits(Command::new("sleep").arg("10"), None, &limits).unwrap();
+        assert!(!output.status.success());
+        assert_eq!(exceeded, Some(LimitExceeded::Timeout(limits.timeout)));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cpu_time_is_limited() {
+        let limits = Limits {
+            timeout: Duration::from_secs(2),
+            ..Limits::default()
+        };
+        let (output, _) =
+            output_with_limits(Command::new("sh").args(["-c", "ulimit -t"]), None, &limits)
+                .unwrap();
+        let cores = thread::available_parallelism().unwrap().get();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            format!("{}\n", 3 * cores)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_output_limit() {
+        let limits = Limits {
+            output: 1024,
+            ..Limits::default()
+        };
+        let (output, exceeded) =
+            output_with_limits(&mut Command::new("yes"), None, &limits).unwrap();
+        assert_eq!(exceeded, Some(LimitExceeded::Output(1024)));
+        assert!(output.stdout.len() <= 1024);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_input_is_written_to_stdin() {
+        let (output, _) =
+            output_with_limits(&mut Command::new("cat"), Some("a,b\n"), &Limits::default())
+                .unwrap();
+        assert_eq!(output.stdout, b"a,b\n");
+    }
+}
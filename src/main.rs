@@ -1,35 +1,52 @@
-use crate::exercise::{Exercise, ExerciseList};
+use crate::cicv::ReportFormat;
+use crate::error::RustlingsError;
+use crate::exercise::{Exercise, Mode};
+use crate::manifest::Severity;
+use crate::progress::Progress;
 use crate::project::RustAnalyzerProject;
-use crate::run::{reset, run};
-use crate::verify::verify;
+use crate::run::run;
+use crate::shell::LineEditor;
+use crate::verify::{verify, verify_solutions};
+use crate::watcher::WatchOptions;
 use argh::FromArgs;
 use console::Emoji;
-use notify::DebouncedEvent;
-use notify::{RecommendedWatcher, RecursiveMode, Watcher};
-use serde::{Deserialize, Serialize};
-use std::ffi::OsStr;
-use std::fs;
-use std::io::{self, prelude::*};
-use std::path::Path;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::mpsc::{channel, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
-use std::time::{UNIX_EPOCH, SystemTime};
 
 #[macro_use]
 mod ui;
 
+mod cache;
+mod cicv;
+mod diagnostics;
+mod diff;
+mod error;
 mod exercise;
+mod expected;
+mod history;
+mod libtest;
+mod limits;
+mod manifest;
+mod pristine;
+mod progress;
 mod project;
 mod run;
+mod rustfmt;
+mod shell;
+mod tui;
 mod verify;
+mod watcher;
 
 // In sync with crate version
 const VERSION: &str = "5.5.1";
 
+// Where `cicvverify` writes its report, this is what the classroom workflow reads
+const DEFAULT_REPORT_PATH: &str = ".github/result/check_result.json";
+
 #[derive(FromArgs, PartialEq, Debug)]
 /// Rustlings is a collection of small exercises to get you used to writing and reading Rust code
 struct Args {
@@ -39,6 +56,9 @@ struct Args {
     /// show the executable version
     #[argh(switch, short = 'v')]
     version: bool,
+    /// check every exercise again, even if it passed before and didn't change
+    #[argh(switch)]
+    no_cache: bool,
     #[argh(subcommand)]
     nested: Option<Subcommands>,
 }
@@ -50,20 +70,52 @@ enum Subcommands {
     Watch(WatchArgs),
     Run(RunArgs),
     Reset(ResetArgs),
+    Diff(DiffArgs),
+    History(HistoryArgs),
+    Restore(RestoreArgs),
+    Skip(SkipArgs),
+    Fmt(FmtArgs),
     Hint(HintArgs),
     List(ListArgs),
     Lsp(LspArgs),
-    CicvVerify(CicvVerifyArgs)
+    CicvVerify(CicvVerifyArgs),
+    CheckManifest(CheckManifestArgs),
+    Solution(SolutionArgs),
+    Cache(CacheArgs),
 }
 
 #[derive(FromArgs, PartialEq, Debug)]
 #[argh(subcommand, name = "cicvverify", description = "cicvverify")]
-struct CicvVerifyArgs {}
+struct CicvVerifyArgs {
+    #[argh(option, short = 'j')]
+    /// the number of exercises graded at the same time,
+    /// defaults to the number of available CPUs
+    jobs: Option<usize>,
+    #[argh(option, short = 'o', default = "PathBuf::from(DEFAULT_REPORT_PATH)")]
+    /// where to write the grading report,
+    /// defaults to .github/result/check_result.json
+    output: PathBuf,
+    #[argh(option, short = 'f', default = "ReportFormat::Json")]
+    /// the format of the grading report: json, junit, tap or markdown
+    format: ReportFormat,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "check-manifest")]
+/// Checks info.toml for mistakes like duplicate names or missing files
+struct CheckManifestArgs {}
 
 #[derive(FromArgs, PartialEq, Debug)]
 #[argh(subcommand, name = "verify")]
 /// Verifies all exercises according to the recommended order
-struct VerifyArgs {}
+struct VerifyArgs {
+    #[argh(switch)]
+    /// check that the reference solutions pass instead of the exercises
+    solutions: bool,
+    #[argh(option, short = 't')]
+    /// only work on the exercises of the given track
+    track: Option<String>,
+}
 
 #[derive(FromArgs, PartialEq, Debug)]
 #[argh(subcommand, name = "watch")]
@@ -72,6 +124,18 @@ struct WatchArgs {
     /// show hints on success
     #[argh(switch)]
     success_hints: bool,
+    /// use a full-screen interface instead of the line-based shell
+    #[argh(switch)]
+    tui: bool,
+    #[argh(option, short = 't')]
+    /// only work on the exercises of the given track
+    track: Option<String>,
+    /// check the files for changes every second, for file systems that don't report them
+    #[argh(switch)]
+    poll: bool,
+    /// ignore changes to files matching the glob, on top of `tempCodeRunnerFile`s
+    #[argh(option)]
+    ignore: Vec<String>,
 }
 
 #[derive(FromArgs, PartialEq, Debug)]
@@ -81,17 +145,77 @@ struct RunArgs {
     #[argh(positional)]
     /// the name of the exercise
     name: String,
+    #[argh(option)]
+    /// only run the tests whose names contain the filter
+    test: Option<String>,
 }
 
 #[derive(FromArgs, PartialEq, Debug)]
 #[argh(subcommand, name = "reset")]
-/// Resets a single exercise using "git stash -- <filename>"
+/// Resets exercises to their original code
 struct ResetArgs {
+    #[argh(positional)]
+    /// the name of the exercise
+    name: Option<String>,
+    #[argh(option, short = 't')]
+    /// reset all exercises of the given track
+    track: Option<String>,
+    #[argh(switch)]
+    /// reset all exercises
+    all: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "fmt")]
+/// Formats an exercise the way rustfmt formats it
+struct FmtArgs {
     #[argh(positional)]
     /// the name of the exercise
     name: String,
 }
 
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "skip")]
+/// Puts an exercise off until all others are done
+struct SkipArgs {
+    #[argh(positional)]
+    /// the name of the exercise
+    name: String,
+    #[argh(switch)]
+    /// bring a skipped exercise back to its place
+    undo: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "diff")]
+/// Shows your changes to an exercise
+struct DiffArgs {
+    #[argh(positional)]
+    /// the name of the exercise
+    name: String,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "history")]
+/// Lists the versions of an exercise saved while watching
+struct HistoryArgs {
+    #[argh(positional)]
+    /// the name of the exercise
+    name: String,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "restore")]
+/// Restores a version of an exercise saved while watching
+struct RestoreArgs {
+    #[argh(positional)]
+    /// the name of the exercise
+    name: String,
+    #[argh(positional)]
+    /// the number of the version, as shown by `history`
+    number: usize,
+}
+
 #[derive(FromArgs, PartialEq, Debug)]
 #[argh(subcommand, name = "hint")]
 /// Returns a hint for the given exercise
@@ -101,6 +225,37 @@ struct HintArgs {
     name: String,
 }
 
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "solution")]
+/// Shows the reference solution of an exercise you have verified
+struct SolutionArgs {
+    #[argh(positional)]
+    /// the name of the exercise
+    name: String,
+    #[argh(switch, short = 'd')]
+    /// show the differences between your code and the solution
+    diff: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "cache")]
+/// Manages the results of exercises that passed before
+struct CacheArgs {
+    #[argh(subcommand)]
+    nested: CacheSubcommands,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand)]
+enum CacheSubcommands {
+    Clear(CacheClearArgs),
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "clear")]
+/// Forgets all cached results, so every exercise is checked again
+struct CacheClearArgs {}
+
 #[derive(FromArgs, PartialEq, Debug)]
 #[argh(subcommand, name = "lsp")]
 /// Enable rust-analyzer for exercises
@@ -126,32 +281,14 @@ struct ListArgs {
     #[argh(switch, short = 's')]
     /// display only exercises that have been solved
     solved: bool,
-}
-
-#[derive(Deserialize, Serialize)]
-pub struct ExerciseCheckList {
-    pub exercises: Vec<ExerciseResult>,
-    pub user_name: Option<String>,
-    pub statistics: ExerciseStatistics
-}
-
-#[derive(Deserialize, Serialize)]
-pub struct ExerciseResult {
-    pub name: String,
-    pub result: bool
-}
-
-#[derive(Deserialize, Serialize)]
-pub struct  ExerciseStatistics {
-    pub total_exercations: usize,
-    pub total_succeeds: usize,
-    pub total_failures: usize,
-    pub total_time: u32,
+    #[argh(option, short = 't')]
+    /// only work on the exercises of the given track
+    track: Option<String>,
 }
 
 #[tokio::main]
 async fn main() {
-    let args: Args = argh::from_env();
+    let args = parse_args();
 
     if args.version {
         println!("v{VERSION}");
@@ -162,24 +299,29 @@ async fn main() {
         println!("\n{WELCOME}\n");
     }
 
+    if args.no_cache {
+        cache::disable();
+    }
+
     if !Path::new("info.toml").exists() {
-        println!(
-            "{} must be run from the rustlings directory",
+        exit_with(RustlingsError::usage(format!(
+            "{} must be run from the rustlings directory\nTry `cd rustlings/`!",
             std::env::current_exe().unwrap().to_str().unwrap()
-        );
-        println!("Try `cd rustlings/`!");
-        std::process::exit(1);
+        )));
+    }
+
+    if let Some(Subcommands::CheckManifest(_)) = args.nested {
+        check_manifest();
     }
 
     if !rustc_exists() {
-        println!("We cannot find `rustc`.");
-        println!("Try running `rustc --version` to diagnose your problem.");
-        println!("For instructions on how to install Rust, check the README.");
-        std::process::exit(1);
+        exit_with(RustlingsError::ToolchainMissing {
+            tool: "rustc".to_string(),
+        });
     }
 
-    let toml_str = &fs::read_to_string("info.toml").unwrap();
-    let exercises = toml::from_str::<ExerciseList>(toml_str).unwrap().exercises;
+    let exercises = manifest::load(Path::new("info.toml")).unwrap_or_else(|e| exit_with(e));
+    pristine::snapshot(&exercises);
     let verbose = args.nocapture;
 
     let command = args.nested.unwrap_or_else(|| {
@@ -188,26 +330,33 @@ async fn main() {
     });
     match command {
         Subcommands::List(subargs) => {
+            let exercises = select_track(exercises, subargs.track.as_deref());
             if !subargs.paths && !subargs.names {
                 println!("{:<17}\t{:<46}\t{:<7}", "Name", "Path", "Status");
             }
             let mut exercises_done: u16 = 0;
+            let progress = Progress::load();
             let filters = subargs.filter.clone().unwrap_or_default().to_lowercase();
             exercises.iter().for_each(|e| {
                 let fname = format!("{}", e.path.display());
                 let filter_cond = filters
                     .split(',')
                     .filter(|f| !f.trim().is_empty())
-                    .any(|f| e.name.contains(&f) || fname.contains(&f));
-                let status = if e.looks_done() {
+                    .any(|f| e.name.contains(f) || fname.contains(f));
+                let verified = progress.is_verified(e);
+                let status = if verified {
                     exercises_done += 1;
                     "Done"
-                } else {
+                } else if progress.is_skipped(e) {
+                    "Skipped"
+                } else if progress.is_unlocked(e) {
                     "Pending"
+                } else {
+                    "Locked"
                 };
                 let solve_cond = {
-                    (e.looks_done() && subargs.solved)
-                        || (!e.looks_done() && subargs.unsolved)
+                    (verified && subargs.solved)
+                        || (!verified && subargs.unsolved)
                         || (!subargs.solved && !subargs.unsolved)
                 };
                 if solve_cond && (filter_cond || subargs.filter.is_none()) {
@@ -227,7 +376,7 @@ async fn main() {
                         handle.write_all(line.as_bytes()).unwrap_or_else(|e| {
                             match e.kind() {
                                 std::io::ErrorKind::BrokenPipe => std::process::exit(0),
-                                _ => std::process::exit(1),
+                                _ => exit_with(RustlingsError::io("Failed to write the list", e)),
                             };
                         });
                     }
@@ -245,93 +394,170 @@ async fn main() {
 
         Subcommands::Run(subargs) => {
             let exercise = find_exercise(&subargs.name, &exercises);
-            run(exercise, verbose).unwrap_or_else(|_| std::process::exit(1));
+            if subargs.test.is_some()
+                && !matches!(exercise.mode, Mode::Test | Mode::Doctest | Mode::Perf)
+            {
+                exit_with(RustlingsError::usage(format!(
+                    "{} has no tests to choose from.",
+                    exercise.name
+                )));
+            }
+            run(exercise, verbose, subargs.test.as_deref()).unwrap_or_else(|e| exit_with(e));
         }
 
-        Subcommands::Reset(subargs) => {
+        Subcommands::Reset(subargs) => match (subargs.name, subargs.track, subargs.all) {
+            (Some(name), None, false) => {
+                let exercise = find_exercise(&name, &exercises);
+                pristine::reset(exercise).unwrap_or_else(|e| exit_with(e));
+                println!("Reset {}", exercise.name);
+            }
+            (None, Some(track), false) => {
+                reset_changed(&select_track(exercises, Some(&track)))
+                    .unwrap_or_else(|e| exit_with(e));
+            }
+            (None, None, true) => reset_changed(&exercises).unwrap_or_else(|e| exit_with(e)),
+            _ => {
+                exit_with(RustlingsError::usage(
+                    "Pass either the name of an exercise, `--track <track>` or `--all`.",
+                ));
+            }
+        },
+
+        Subcommands::Skip(subargs) => {
             let exercise = find_exercise(&subargs.name, &exercises);
+            let progress = Progress::load();
+            if subargs.undo {
+                if !progress.is_skipped(exercise) {
+                    exit_with(RustlingsError::usage(format!(
+                        "{} isn't skipped.",
+                        exercise.name
+                    )));
+                }
+                set_skipped(exercise, false).unwrap_or_else(|e| exit_with(e));
+                println!("{} is back in its place.", exercise.name);
+            } else {
+                if progress.is_verified(exercise) {
+                    exit_with(RustlingsError::usage(format!(
+                        "{} is done already.",
+                        exercise.name
+                    )));
+                }
+                set_skipped(exercise, true).unwrap_or_else(|e| exit_with(e));
+                println!(
+                    "Skipped {0}, it comes after all other exercises now. Run `rustlings skip --undo {0}` to bring it back.",
+                    exercise.name
+                );
+            }
+        }
 
-            reset(exercise).unwrap_or_else(|_| std::process::exit(1));
+        Subcommands::Fmt(subargs) => {
+            let exercise = find_exercise(&subargs.name, &exercises);
+            if rustfmt::check(exercise).is_ok() {
+                println!("{} is formatted already.", exercise.name);
+            } else if let Err(err) = rustfmt::apply(exercise) {
+                // rustfmt explains what it can't parse
+                if let Some(output) = err.output() {
+                    eprint!("{}", output.stderr);
+                }
+                exit_with(err);
+            } else {
+                println!("Formatted {}", exercise.name);
+            }
         }
 
-        Subcommands::Hint(subargs) => {
+        Subcommands::Diff(subargs) => {
             let exercise = find_exercise(&subargs.name, &exercises);
+            let changes = pristine::changes(exercise).unwrap_or_else(|e| exit_with(e));
+            if changes.is_empty() {
+                println!("You haven't changed {} yet.", exercise.name);
+            } else {
+                print!("{changes}");
+            }
+        }
 
-            println!("{}", exercise.hint);
+        Subcommands::History(subargs) => {
+            let exercise = find_exercise(&subargs.name, &exercises);
+            let snapshots = history::load(exercise);
+            if snapshots.is_empty() {
+                println!(
+                    "There is no history of {} yet, it is saved while `rustlings watch` runs.",
+                    exercise.name
+                );
+            }
+            for (i, snapshot) in snapshots.iter().enumerate() {
+                let line = format!(
+                    "{:>3}  {:<16}  {:<9}  {}",
+                    i + 1,
+                    history::ago(snapshot.saved_at),
+                    snapshot.outcome,
+                    snapshot.error_code.as_deref().unwrap_or_default()
+                );
+                println!("{}", line.trim_end());
+            }
         }
 
-        Subcommands::Verify(_subargs) => {
-            verify(&exercises, (0, exercises.len()), verbose, false)
-                .unwrap_or_else(|_| std::process::exit(1));
+        Subcommands::Restore(subargs) => {
+            let exercise = find_exercise(&subargs.name, &exercises);
+            if !history::restore(exercise, subargs.number).unwrap_or_else(|e| exit_with(e)) {
+                exit_with(RustlingsError::usage(format!(
+                    "There is no version {} of {}, see `rustlings history {}`.",
+                    subargs.number, exercise.name, exercise.name
+                )));
+            }
+            println!("Restored version {} of {}", subargs.number, exercise.name);
         }
 
-        Subcommands::CicvVerify(_subargs) => {
-            // let toml_str = &fs::read_to_string("info.toml").unwrap();
-            // exercises = toml::from_str::<ExerciseList>(toml_str).unwrap().exercises;
-            let now_start = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-            let rights = Arc::new(Mutex::new(0));
-            let alls = exercises.len();
-
-            let exercise_check_list =  Arc::new(Mutex::new(
-                ExerciseCheckList {
-                    exercises: vec![], 
-                    user_name:  None, 
-                    statistics: ExerciseStatistics { 
-                        total_exercations: alls, 
-                        total_succeeds: 0, 
-                        total_failures: 0, 
-                        total_time: 0, 
-                    }
-                }
-            ));
-
-            let mut tasks = vec![];
-            for exercise in exercises {
-                let now_start = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-                let inner_exercise = exercise;
-                let c_mutex = Arc::clone(&rights);
-                let exercise_check_list_ref = Arc::clone(&exercise_check_list);
-                let _verbose = verbose.clone();
-                let t = tokio::task::spawn( async move {
-                    match run(&inner_exercise, true) {
-                    // match verify(vec![&inner_exercise], (0, 1), true, true) {
-                        Ok(_) => {
-                            *c_mutex.lock().unwrap() += 1;
-                            println!("{}执行成功", inner_exercise.name);
-                            println!("总的题目数: {}", alls);
-                            println!("当前做正确的题目数: {}", *c_mutex.lock().unwrap());
-                            let now_end = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-                            println!("当前修改试卷耗时: {} s", now_end - now_start);
-                            exercise_check_list_ref.lock().unwrap().exercises.push(ExerciseResult{ 
-                                name: inner_exercise.name, result: true,
-                            });
-                            exercise_check_list_ref.lock().unwrap().statistics.total_succeeds += 1;
-                        },
-                        Err(_) => {
-                            println!("{}执行失败", inner_exercise.name);
-                            println!("总的题目数: {}", alls);
-                            println!("当前做正确的题目数: {}", *c_mutex.lock().unwrap());
-                            let now_end = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-                            println!("当前修改试卷耗时: {} s", now_end - now_start);
-                            exercise_check_list_ref.lock().unwrap().exercises.push(ExerciseResult{ 
-                                name: inner_exercise.name, result: false,
-                            });
-                            exercise_check_list_ref.lock().unwrap().statistics.total_failures += 1;
-                        }
-                    }
+        Subcommands::Hint(subargs) => {
+            let exercise = find_exercise(&subargs.name, &exercises);
+            show_hint(exercise);
+        }
+
+        Subcommands::Cache(subargs) => match subargs.nested {
+            CacheSubcommands::Clear(_) => {
+                cache::clear().unwrap_or_else(|e| {
+                    exit_with(RustlingsError::io("Failed to clear the cache", e))
                 });
-                tasks.push(t);
-            }
-            for task in tasks { task.await.unwrap(); }
-            let now_end = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-            let total_time = now_end - now_start;
-            println!("===============================试卷批改完成,总耗时: {} s; ==================================", total_time);
-            let exercise_check_list_ref = Arc::clone(&exercise_check_list);
-            exercise_check_list_ref.lock().unwrap().statistics.total_time = total_time as u32;
-            let serialized = serde_json::to_string_pretty(&*exercise_check_list.lock().unwrap()).unwrap();
-            fs::write(".github/result/check_result.json", serialized).unwrap();
+                println!("The cache is empty now.");
+            }
         },
 
+        Subcommands::Solution(subargs) => {
+            let exercise = find_exercise(&subargs.name, &exercises);
+            show_solution(exercise, subargs.diff).unwrap_or_else(|e| exit_with(e));
+        }
+
+        Subcommands::Verify(subargs) => {
+            let exercises = select_track(exercises, subargs.track.as_deref());
+            if subargs.solutions {
+                verify_solutions(&exercises).unwrap_or_else(|e| exit_with(e));
+            } else {
+                let in_order = Progress::load().in_order(&exercises);
+                verify(in_order, (0, exercises.len()), verbose, false)
+                    .unwrap_or_else(|e| exit_with(e));
+            }
+        }
+
+        Subcommands::CicvVerify(subargs) => {
+            let jobs = subargs.jobs.unwrap_or_else(cicv::default_jobs);
+            let check_list = cicv::grade(exercises, jobs).await;
+            println!(
+                "===============================试卷批改完成,总耗时: {} s; ==================================",
+                check_list.statistics.total_time
+            );
+            if let Err(e) = check_list.write(&subargs.output, subargs.format) {
+                exit_with(RustlingsError::io(
+                    format!(
+                        "Failed to write the grading report to {}",
+                        subargs.output.display()
+                    ),
+                    e,
+                ));
+            }
+        }
+
+        // Handled before the manifest is loaded, since loading it fails on errors
+        Subcommands::CheckManifest(_subargs) => check_manifest(),
+
         Subcommands::Lsp(_subargs) => {
             let mut project = RustAnalyzerProject::new();
             project
@@ -351,61 +577,282 @@ async fn main() {
             }
         }
 
-        Subcommands::Watch(_subargs) => match watch(&exercises, verbose, _subargs.success_hints) {
-            Err(e) => {
-                println!(
-                    "Error: Could not watch your progress. Error message was {:?}.",
-                    e
-                );
-                println!("Most likely you've run out of disk space or your 'inotify limit' has been reached.");
-                std::process::exit(1);
-            }
-            Ok(WatchStatus::Finished) => {
-                println!(
-                    "{emoji} All exercises completed! {emoji}",
-                    emoji = Emoji("🎉", "★")
-                );
-                println!("\n{FENISH_LINE}\n");
+        Subcommands::Watch(subargs) => {
+            let exercises = select_track(exercises, subargs.track.as_deref());
+            let use_tui = subargs.tui && tui::is_supported();
+            if subargs.tui && !use_tui {
+                println!("Your terminal can't show the full-screen interface, falling back to the shell.");
             }
-            Ok(WatchStatus::Unfinished) => {
-                println!("We hope you're enjoying learning about Rust!");
-                println!("If you want to continue working on the exercises at a later point, you can simply run `rustlings watch` again");
+            let options = WatchOptions::new(subargs.poll, &subargs.ignore).unwrap_or_else(|e| {
+                exit_with(RustlingsError::usage(format!(
+                    "Invalid ignore pattern: {e}"
+                )))
+            });
+            let status = if use_tui {
+                tui::watch(&exercises, options)
+            } else {
+                watch(&exercises, options, verbose, subargs.success_hints)
+            };
+            match status {
+                Err(e) => {
+                    println!("Most likely you've run out of disk space or your 'inotify limit' has been reached.");
+                    let source = match e {
+                        notify::Error::Io(e) => e,
+                        e => std::io::Error::other(e),
+                    };
+                    exit_with(RustlingsError::io("Could not watch your progress", source));
+                }
+                Ok(WatchStatus::Finished) => {
+                    println!(
+                        "{emoji} All exercises completed! {emoji}",
+                        emoji = Emoji("🎉", "★")
+                    );
+                    println!("\n{FENISH_LINE}\n");
+                }
+                Ok(WatchStatus::Unfinished) => {
+                    println!("We hope you're enjoying learning about Rust!");
+                    println!("If you want to continue working on the exercises at a later point, you can simply run `rustlings watch` again");
+                }
             }
-        },
+        }
     }
 }
 
+// The commands of the watch shell, for completion
+const SHELL_COMMANDS: &[&str] = &[
+    "hint", "next", "prev", "goto", "focus", "skip", "run", "reset", "explain", "list", "clear",
+    "quit", "help",
+];
+
+// What the watch shell shares with the watch loop
+#[derive(Default)]
+struct ShellState {
+    // The exercise the commands apply to: the one that failed last,
+    // or the one moved to with `next`, `prev` or `goto`
+    current: Option<Exercise>,
+    // The only exercise verified when files change, set with `focus`
+    focus: Option<Exercise>,
+    // The code of the last compiler error seen, for `explain`
+    error_code: Option<String>,
+}
+
+impl ShellState {
+    // Remember the outcome of checking the exercise
+    fn update(&mut self, exercise: Option<Exercise>, result: &Result<(), RustlingsError>) {
+        if let Some(exercise) = exercise {
+            self.current = Some(exercise);
+        }
+        if let Some(code) = result
+            .as_ref()
+            .err()
+            .and_then(|e| e.output())
+            .and_then(|output| diagnostics::first_error_code(&output.diagnostics))
+        {
+            self.error_code = Some(code.to_string());
+        }
+    }
+}
+
+// What the watch loop reacts to
+enum WatchEvent {
+    // A file of the exercises changed
+    Changed(PathBuf),
+    // Verify the exercise with the name, after moving to it in the shell
+    Check(String),
+    // Run the current exercise
+    Run,
+    Quit,
+}
+
 fn spawn_watch_shell(
-    failed_exercise_hint: &Arc<Mutex<Option<String>>>,
-    should_quit: Arc<AtomicBool>,
+    exercises: &[Exercise],
+    state: &Arc<Mutex<ShellState>>,
+    events: Sender<WatchEvent>,
 ) {
-    let failed_exercise_hint = Arc::clone(failed_exercise_hint);
+    let exercises = exercises.to_vec();
+    let state = Arc::clone(state);
+    let names = exercises.iter().map(|e| e.name.clone()).collect();
+    let mut editor = LineEditor::new(SHELL_COMMANDS, names);
     println!("Welcome to watch mode! You can type 'help' to get an overview of the commands you can use here.");
     thread::spawn(move || loop {
-        let mut input = String::new();
-        match io::stdin().read_line(&mut input) {
-            Ok(_) => {
-                let input = input.trim();
-                if input == "hint" {
-                    if let Some(hint) = &*failed_exercise_hint.lock().unwrap() {
-                        println!("{hint}");
+        let input = match editor.read_line() {
+            Ok(Some(input)) => input,
+            Ok(None) => break,
+            Err(error) => {
+                println!("error reading command: {error}");
+                break;
+            }
+        };
+        let input = input.trim();
+        let (command, argument) = input.split_once(' ').unwrap_or((input, ""));
+        let argument = argument.trim();
+        let current = state.lock().unwrap().current.clone();
+        let find = |name: &str| exercises.iter().find(|e| e.name == name);
+        let sent = match command {
+            "" => Ok(()),
+            "hint" => {
+                match &current {
+                    Some(exercise) => show_hint(exercise),
+                    None => println!("There is no current exercise, go to one with `goto <name>`."),
+                }
+                Ok(())
+            }
+            "next" | "prev" => {
+                let position = current
+                    .as_ref()
+                    .and_then(|current| exercises.iter().position(|e| e.name == current.name));
+                let target = match (command, position) {
+                    ("next", Some(i)) => exercises.get(i + 1),
+                    ("prev", Some(i)) => i.checked_sub(1).and_then(|i| exercises.get(i)),
+                    (_, None) => next_to_do(&exercises, None),
+                    _ => unreachable!(),
+                };
+                match target {
+                    Some(exercise) => events.send(WatchEvent::Check(exercise.name.clone())),
+                    None => {
+                        println!("There is no {command} exercise.");
+                        Ok(())
+                    }
+                }
+            }
+            "goto" | "focus" if !argument.is_empty() => match find(argument) {
+                Some(exercise) => {
+                    if command == "focus" {
+                        state.lock().unwrap().focus = Some(exercise.clone());
+                        println!("Only {} is verified when files change now, type `focus` to verify all of them again.", exercise.name);
+                    }
+                    events.send(WatchEvent::Check(exercise.name.clone()))
+                }
+                None => {
+                    println!("No exercise found for '{argument}'!");
+                    Ok(())
+                }
+            },
+            "goto" => {
+                println!("Type the name of the exercise to go to, like `goto intro1`.");
+                Ok(())
+            }
+            "focus" => {
+                state.lock().unwrap().focus = None;
+                println!("All exercises are verified when files change again.");
+                Ok(())
+            }
+            "skip" => {
+                let exercise = match argument {
+                    "" => current.as_ref(),
+                    name => find(name),
+                };
+                match exercise {
+                    Some(exercise) if Progress::load().is_verified(exercise) => {
+                        println!("{} is done already.", exercise.name);
+                        Ok(())
+                    }
+                    Some(exercise) => {
+                        if let Err(e) = set_skipped(exercise, true) {
+                            warn!("{}", e);
+                        }
+                        println!(
+                            "Skipped {}, it comes after all other exercises now.",
+                            exercise.name
+                        );
+                        match next_to_do(&exercises, Some(exercise)) {
+                            Some(next) => events.send(WatchEvent::Check(next.name.clone())),
+                            None => {
+                                println!("There is no other exercise left to do.");
+                                Ok(())
+                            }
+                        }
                     }
-                } else if input == "clear" {
-                    println!("\x1B[2J\x1B[1;1H");
-                } else if input.eq("quit") {
-                    should_quit.store(true, Ordering::SeqCst);
-                    println!("Bye!");
-                } else if input.eq("help") {
-                    println!("Commands available to you in watch mode:");
-                    println!("  hint   - prints the current exercise's hint");
-                    println!("  clear  - clears the screen");
-                    println!("  quit   - quits watch mode");
-                    println!("  !<cmd> - executes a command, like `!rustc --explain E0381`");
-                    println!("  help   - displays this help message");
-                    println!();
-                    println!("Watch mode automatically re-evaluates the current exercise");
-                    println!("when you edit a file's contents.")
-                } else if let Some(cmd) = input.strip_prefix('!') {
+                    None if argument.is_empty() => {
+                        println!("There is no current exercise, go to one with `goto <name>`.");
+                        Ok(())
+                    }
+                    None => {
+                        println!("No exercise found for '{argument}'!");
+                        Ok(())
+                    }
+                }
+            }
+            "run" => events.send(WatchEvent::Run),
+            "reset" => {
+                match &current {
+                    Some(exercise) => match pristine::reset(exercise) {
+                        Ok(()) => println!("Reset {}", exercise.name),
+                        Err(e) => warn!("{}", e),
+                    },
+                    None => println!("There is no current exercise, go to one with `goto <name>`."),
+                }
+                Ok(())
+            }
+            "explain" => {
+                match state.lock().unwrap().error_code.clone() {
+                    Some(code) => {
+                        if let Err(e) = Command::new("rustc").args(["--explain", &code]).status() {
+                            println!("failed to execute command `rustc --explain {code}`: {e}");
+                        }
+                    }
+                    None => println!("There was no error with a code to explain yet."),
+                }
+                Ok(())
+            }
+            "list" => {
+                let progress = Progress::load();
+                let focus = state.lock().unwrap().focus.clone();
+                for exercise in &exercises {
+                    let marker = match (&current, &focus) {
+                        (_, Some(focus)) if focus.name == exercise.name => "*",
+                        (Some(current), _) if current.name == exercise.name => ">",
+                        _ => " ",
+                    };
+                    let status = if progress.is_verified(exercise) {
+                        "Done"
+                    } else if progress.is_skipped(exercise) {
+                        "Skipped"
+                    } else if progress.is_unlocked(exercise) {
+                        "Pending"
+                    } else {
+                        "Locked"
+                    };
+                    println!("{marker} {:<17}\t{status}", exercise.name);
+                }
+                Ok(())
+            }
+            "clear" => {
+                println!("\x1B[2J\x1B[1;1H");
+                Ok(())
+            }
+            "quit" => {
+                println!("Bye!");
+                let _ = events.send(WatchEvent::Quit);
+                break;
+            }
+            "help" => {
+                println!("Commands available to you in watch mode:");
+                println!("  hint          - prints the next hint of the current exercise");
+                println!("  next, prev    - goes to the next or previous exercise");
+                println!("  goto <name>   - goes to the given exercise");
+                println!("  focus <name>  - only verifies the given exercise when files change,");
+                println!("                  `focus` alone verifies all of them again");
+                println!(
+                    "  skip          - puts the current exercise off until all others are done,"
+                );
+                println!("                  `goto <name>` takes you back to it");
+                println!("  run           - runs the current exercise");
+                println!("  reset         - resets the current exercise to its original code");
+                println!("  explain       - explains the last compiler error");
+                println!("  list          - lists the exercises, > marks the current one and * the focused one");
+                println!("  clear         - clears the screen");
+                println!("  quit          - quits watch mode");
+                println!("  !<cmd>        - executes a command, like `!rustc --explain E0381`");
+                println!("  help          - displays this help message");
+                println!();
+                println!("Watch mode automatically re-evaluates the current exercise");
+                println!("when you edit a file's contents. Press Tab to complete commands");
+                println!("and exercise names, and the arrow keys to recall earlier commands.");
+                Ok(())
+            }
+            _ => {
+                if let Some(cmd) = input.strip_prefix('!') {
                     let parts: Vec<&str> = cmd.split_whitespace().collect();
                     if parts.is_empty() {
                         println!("no command provided");
@@ -415,29 +862,72 @@ fn spawn_watch_shell(
                 } else {
                     println!("unknown command: {input}");
                 }
+                Ok(())
             }
-            Err(error) => println!("error reading command: {error}"),
+        };
+        // The watch loop is gone once it finished
+        if sent.is_err() {
+            break;
         }
     });
 }
 
+// The first exercise to work on that isn't done yet, other than `except`
+fn next_to_do<'a>(exercises: &'a [Exercise], except: Option<&Exercise>) -> Option<&'a Exercise> {
+    let progress = Progress::load();
+    progress.in_order(exercises).into_iter().find(|e| {
+        !progress.is_verified(e)
+            && progress.is_unlocked(e)
+            && !matches!(except, Some(except) if except.name == e.name)
+    })
+}
+
+// Defer the exercise until all others are done, or bring it back to its place
+fn set_skipped(exercise: &Exercise, skipped: bool) -> Result<(), RustlingsError> {
+    let mut progress = Progress::load();
+    progress.set_skipped(exercise, skipped);
+    progress
+        .save()
+        .map_err(|e| RustlingsError::io("Failed to record your progress", e))
+}
+
+// The exercises of the given track, or all of them when no track is given
+fn select_track(exercises: Vec<Exercise>, track: Option<&str>) -> Vec<Exercise> {
+    let track = match track {
+        Some(track) => track,
+        None => return exercises,
+    };
+    let mut tracks: Vec<&str> = Vec::new();
+    for exercise in &exercises {
+        if !tracks.contains(&exercise.track.as_str()) {
+            tracks.push(&exercise.track);
+        }
+    }
+    if !tracks.contains(&track) {
+        exit_with(RustlingsError::usage(format!(
+            "No track found for '{track}'!\nAvailable tracks: {}",
+            tracks.join(", ")
+        )));
+    }
+    exercises.into_iter().filter(|e| e.track == track).collect()
+}
+
 fn find_exercise<'a>(name: &str, exercises: &'a [Exercise]) -> &'a Exercise {
     if name.eq("next") {
-        exercises
-            .iter()
-            .find(|e| !e.looks_done())
-            .unwrap_or_else(|| {
-                println!("🎉 Congratulations! You have done all the exercises!");
-                println!("🔚 There are no more exercises to do next!");
-                std::process::exit(1)
-            })
+        next_to_do(exercises, None).unwrap_or_else(|| {
+            println!("🎉 Congratulations! You have done all the exercises!");
+            exit_with(RustlingsError::usage(
+                "🔚 There are no more exercises to do next!",
+            ))
+        })
     } else {
         exercises
             .iter()
             .find(|e| e.name == name)
             .unwrap_or_else(|| {
-                println!("No exercise found for '{name}'!");
-                std::process::exit(1)
+                exit_with(RustlingsError::usage(format!(
+                    "No exercise found for '{name}'!"
+                )))
             })
     }
 }
@@ -449,6 +939,7 @@ enum WatchStatus {
 
 fn watch(
     exercises: &[Exercise],
+    options: WatchOptions,
     verbose: bool,
     success_hints: bool,
 ) -> notify::Result<WatchStatus> {
@@ -458,73 +949,319 @@ fn watch(
         println!("\x1Bc");
     }
 
-    let (tx, rx) = channel();
-    let should_quit = Arc::new(AtomicBool::new(false));
-
-    let mut watcher: RecommendedWatcher = Watcher::new(tx, Duration::from_secs(1))?;
-    watcher.watch(Path::new("./exercises"), RecursiveMode::Recursive)?;
+    let rx = watcher::changed_files(options)?;
+    let (events, rx_events) = channel();
+    let changes = events.clone();
+    thread::spawn(move || {
+        for filepath in rx {
+            if changes.send(WatchEvent::Changed(filepath)).is_err() {
+                break;
+            }
+        }
+    });
 
     clear_screen();
 
-    let to_owned_hint = |t: &Exercise| t.hint.to_owned();
-    let failed_exercise_hint = match verify(
-        exercises.iter(),
+    // The exercise the error is about
+    let to_owned_exercise = |e: &RustlingsError| {
+        e.exercise()
+            .and_then(|name| exercises.iter().find(|ex| ex.name == name))
+            .cloned()
+    };
+    // Progress is reloaded for every exercise, so exercises unlocked
+    // while verifying the ones before them are offered right away
+    let unlocked = |e: &&Exercise| Progress::load().is_unlocked(e);
+    // Everything offered may pass while locked exercises are left,
+    // their prerequisites are outside of the exercises being watched
+    let finished = || {
+        let progress = Progress::load();
+        let locked = exercises
+            .iter()
+            .filter(|e| !progress.is_unlocked(e))
+            .count();
+        if locked > 0 {
+            warn!(
+                "{} exercises are locked, their prerequisites aren't verified yet",
+                locked
+            );
+        }
+        exercises.iter().all(|e| progress.is_verified(e))
+    };
+    let state = Arc::new(Mutex::new(ShellState::default()));
+    match verify(
+        Progress::load()
+            .in_order(exercises)
+            .into_iter()
+            .filter(unlocked),
         (0, exercises.len()),
         verbose,
         success_hints,
     ) {
-        Ok(_) => return Ok(WatchStatus::Finished),
-        Err(exercise) => Arc::new(Mutex::new(Some(to_owned_hint(exercise)))),
-    };
-    spawn_watch_shell(&failed_exercise_hint, Arc::clone(&should_quit));
-    loop {
-        match rx.recv_timeout(Duration::from_secs(1)) {
-            Ok(event) => match event {
-                DebouncedEvent::Create(b) | DebouncedEvent::Chmod(b) | DebouncedEvent::Write(b) => {
-                    if b.extension() == Some(OsStr::new("rs")) && b.exists() {
-                        let filepath = b.as_path().canonicalize().unwrap();
-                        let pending_exercises = exercises
+        Ok(_) if finished() => return Ok(WatchStatus::Finished),
+        Ok(_) => {}
+        Err(e) => {
+            warn_environment_error(&e);
+            let exercise = to_owned_exercise(&e);
+            state.lock().unwrap().update(exercise, &Err(e));
+        }
+    }
+    spawn_watch_shell(exercises, &state, events);
+    while let Ok(event) = rx_events.recv() {
+        match event {
+            WatchEvent::Changed(filepath) => {
+                let progress = Progress::load();
+                let focus = state.lock().unwrap().focus.clone();
+                let pending_exercises: Vec<&Exercise> =
+                    match &focus {
+                        Some(focus) => vec![focus],
+                        None => exercises
                             .iter()
                             .find(|e| filepath.ends_with(&e.path))
                             .into_iter()
-                            .chain(
-                                exercises
-                                    .iter()
-                                    .filter(|e| !e.looks_done() && !filepath.ends_with(&e.path)),
-                            );
-                        let num_done = exercises.iter().filter(|e| e.looks_done()).count();
-                        clear_screen();
-                        match verify(
-                            pending_exercises,
-                            (num_done, exercises.len()),
-                            verbose,
-                            success_hints,
-                        ) {
-                            Ok(_) => return Ok(WatchStatus::Finished),
-                            Err(exercise) => {
-                                let mut failed_exercise_hint = failed_exercise_hint.lock().unwrap();
-                                *failed_exercise_hint = Some(to_owned_hint(exercise));
-                            }
+                            .chain(progress.in_order(exercises).into_iter().filter(|e| {
+                                !progress.is_verified(e) && !filepath.ends_with(&e.path)
+                            }))
+                            .collect(),
+                    };
+                let num_done = exercises.iter().filter(|e| progress.is_verified(e)).count();
+                clear_screen();
+                if let Some(locked) = exercises
+                    .iter()
+                    .find(|e| filepath.ends_with(&e.path) && !progress.is_unlocked(e))
+                {
+                    let missing = progress.missing_prerequisites(locked).join(", ");
+                    warn!(
+                        "{} is locked, verify these exercises first: {missing}",
+                        locked
+                    );
+                }
+                // With a focus the changed exercise may not be verified at all
+                let verified_first = pending_exercises.first().map(|e| e.name.clone());
+                let result = verify(
+                    pending_exercises.into_iter().filter(unlocked),
+                    (num_done, exercises.len()),
+                    verbose,
+                    success_hints,
+                );
+                if let Some(changed) = exercises.iter().find(|e| {
+                    filepath.ends_with(&e.path)
+                        && progress.is_unlocked(e)
+                        && verified_first.as_ref() == Some(&e.name)
+                }) {
+                    record_history(changed, &result);
+                }
+                match result {
+                    Ok(_) if focus.is_none() && finished() => return Ok(WatchStatus::Finished),
+                    Ok(_) => {}
+                    Err(e) => {
+                        warn_environment_error(&e);
+                        let exercise = to_owned_exercise(&e);
+                        state.lock().unwrap().update(exercise, &Err(e));
+                    }
+                }
+            }
+            WatchEvent::Check(name) => {
+                let exercise = find_exercise(&name, exercises);
+                let progress = Progress::load();
+                let num_done = exercises.iter().filter(|e| progress.is_verified(e)).count();
+                clear_screen();
+                let result = if progress.is_unlocked(exercise) {
+                    verify(
+                        [exercise],
+                        (num_done, exercises.len()),
+                        verbose,
+                        success_hints,
+                    )
+                } else {
+                    let missing = progress.missing_prerequisites(exercise).join(", ");
+                    warn!(
+                        "{} is locked, verify these exercises first: {missing}",
+                        exercise
+                    );
+                    Ok(())
+                };
+                if let Err(e) = &result {
+                    warn_environment_error(e);
+                }
+                state
+                    .lock()
+                    .unwrap()
+                    .update(Some(exercise.clone()), &result);
+            }
+            WatchEvent::Run => {
+                let current = state.lock().unwrap().current.clone();
+                match current {
+                    Some(exercise) => {
+                        let result = run(&exercise, verbose, None);
+                        if let Err(e) = &result {
+                            warn_environment_error(e);
                         }
+                        state.lock().unwrap().update(None, &result);
                     }
+                    None => println!("There is no current exercise, go to one with `goto <name>`."),
                 }
-                _ => {}
-            },
-            Err(RecvTimeoutError::Timeout) => {
-                // the timeout expired, just check the `should_quit` variable below then loop again
             }
-            Err(e) => println!("watch error: {e:?}"),
+            WatchEvent::Quit => break,
         }
-        // Check if we need to exit
-        if should_quit.load(Ordering::SeqCst) {
-            return Ok(WatchStatus::Unfinished);
+    }
+    Ok(WatchStatus::Unfinished)
+}
+
+// Add the changed exercise to its history. It is the first exercise
+// verified, so any other exercise failing means it passed.
+fn record_history(changed: &Exercise, result: &Result<(), RustlingsError>) {
+    let result = match result {
+        Err(e) if e.exercise() == Some(&changed.name) => Err(e),
+        Err(e) if e.exercise().is_none() => return,
+        _ => Ok(()),
+    };
+    if let Err(e) = history::record(changed, result) {
+        warn!("Failed to save the history of {}: {e}", changed);
+    }
+}
+
+// Print the hint levels of the exercise revealed so far, including one more
+// than last time, and remember how many were revealed
+fn show_hint(exercise: &Exercise) {
+    let mut progress = Progress::load();
+    let revealed = progress.hints_used(exercise);
+    let used = progress.use_hint(exercise);
+    if let Err(e) = progress.save() {
+        warn!("Failed to record your progress: {}", e);
+    }
+
+    let levels = exercise.hint.levels();
+    if levels.len() == 1 {
+        println!("{}", levels[0]);
+        return;
+    }
+    for (i, level) in levels[..used].iter().enumerate() {
+        println!("Hint {} of {}:", i + 1, levels.len());
+        println!("{level}");
+        println!();
+    }
+    if used == revealed {
+        println!("There are no more hints for {}.", exercise.name);
+    }
+}
+
+// Print the reference solution of the exercise, or how the learner's code
+// differs from it, but only once the learner solved the exercise themselves
+fn show_solution(exercise: &Exercise, diff: bool) -> Result<(), RustlingsError> {
+    let solution_path = match &exercise.solution {
+        Some(path) => path,
+        None => {
+            return Err(RustlingsError::usage(format!(
+                "There is no solution for {} yet.",
+                exercise.name
+            )))
+        }
+    };
+    if !Progress::load().is_verified(exercise) {
+        return Err(RustlingsError::usage(format!(
+            "The solution of {} is only shown once you solved it with `rustlings watch` or `rustlings verify`.",
+            exercise.name
+        )));
+    }
+
+    let read = |path: &Path| {
+        std::fs::read_to_string(path)
+            .map_err(|e| RustlingsError::io(format!("Failed to read {}", path.display()), e))
+    };
+    let solution = read(solution_path)?;
+    if !diff {
+        print!("{solution}");
+        return Ok(());
+    }
+    let changes = diff::unified(
+        &exercise.path.display().to_string(),
+        &solution_path.display().to_string(),
+        &read(&exercise.path)?,
+        &solution,
+    );
+    if changes.is_empty() {
+        success!(
+            "Your code of {} is identical to the solution!",
+            exercise.name
+        );
+    } else {
+        print!("{changes}");
+    }
+    Ok(())
+}
+
+// Reset the exercises that differ from their originals
+fn reset_changed(exercises: &[Exercise]) -> Result<(), RustlingsError> {
+    let mut reset = 0;
+    for exercise in exercises {
+        if pristine::is_changed(exercise)? {
+            pristine::reset(exercise)?;
+            println!("Reset {}", exercise.name);
+            reset += 1;
+        }
+    }
+    println!("Reset {reset} of {} exercises.", exercises.len());
+    Ok(())
+}
+
+// Report every problem in info.toml, failing if any of them is an error
+fn check_manifest() -> ! {
+    let (_, diagnostics) = manifest::check(Path::new("info.toml"));
+    for diagnostic in &diagnostics {
+        diagnostic.print();
+    }
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+    println!("info.toml: {errors} error(s) and {warnings} warning(s)");
+    if errors > 0 {
+        exit_with(RustlingsError::Manifest {
+            message: format!("found {errors} error(s)"),
+        });
+    }
+    std::process::exit(0);
+}
+
+// Parse the command line like `argh::from_env` does, but exit
+// with the code of usage errors when it can't be parsed
+fn parse_args() -> Args {
+    let strings: Vec<String> = std::env::args().collect();
+    let cmd = Path::new(&strings[0])
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(&strings[0]);
+    let strs: Vec<&str> = strings.iter().map(String::as_str).collect();
+    Args::from_args(&[cmd], &strs[1..]).unwrap_or_else(|early_exit| match early_exit.status {
+        Ok(()) => {
+            println!("{}", early_exit.output);
+            std::process::exit(0);
         }
+        Err(()) => exit_with(RustlingsError::usage(format!(
+            "{}\nRun {cmd} --help for more information.",
+            early_exit.output.trim_end()
+        ))),
+    })
+}
+
+// Report the error and terminate with the exit code belonging to it
+fn exit_with(err: RustlingsError) -> ! {
+    eprintln!("error: {err}");
+    std::process::exit(err.exit_code());
+}
+
+// Errors about the exercise itself were already reported while verifying it,
+// but watch mode shouldn't silently keep going when the environment is broken.
+fn warn_environment_error(err: &RustlingsError) {
+    if err.exercise().is_none() {
+        warn!("{}", err);
     }
 }
 
 fn rustc_exists() -> bool {
     Command::new("rustc")
-        .args(&["--version"])
+        .args(["--version"])
         .stdout(Stdio::null())
         .spawn()
         .and_then(|mut child| child.wait())
@@ -0,0 +1,556 @@
+use crate::error::RustlingsError;
+use crate::exercise::{Exercise, ExerciseList, Hint, Mode, I_AM_DONE_REGEX};
+use crate::expected::ExpectedStdout;
+use console::style;
+use glob::{glob, Pattern};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// The keys every exercise entry must have
+const REQUIRED_KEYS: &[&str] = &["name", "path", "mode", "hint"];
+// Keys that only make a difference in some modes
+const KEYS_OF_MODES: &[(&str, &[&str])] = &[
+    ("expected_stdout", &["compile"]),
+    ("expected_exit_code", &["compile"]),
+    ("args", &["compile", "clippy"]),
+    ("error_codes", &["compile_fail"]),
+    ("max_duration", &["perf"]),
+];
+// Source files under exercises/ that aren't exercises themselves
+const SUPPORT_FILES: &[&str] = &["mod.rs", "build.rs"];
+// The track of the exercises in the top-level info.toml
+pub const DEFAULT_TRACK: &str = "main";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Severity {
+    // The manifest can't be used like this
+    Error,
+    // The manifest works, but probably isn't what its author meant
+    Warning,
+}
+
+// A problem found in a manifest
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    // The file the problem is in
+    pub file: PathBuf,
+    // The 1-based line and column the problem is at, if it is at a specific place
+    pub location: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some((line, column)) = self.location {
+            write!(f, ":{line}:{column}")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl Diagnostic {
+    // Print the diagnostic the way compilers do
+    pub fn print(&self) {
+        let label = match self.severity {
+            Severity::Error => style("error").red().bold(),
+            Severity::Warning => style("warning").yellow().bold(),
+        };
+        println!("{label}: {self}");
+    }
+}
+
+// Load the exercises of the manifest, failing if it contains any errors.
+// Warnings are left for `rustlings check-manifest` to report.
+pub fn load(path: &Path) -> Result<Vec<Exercise>, RustlingsError> {
+    let (exercises, diagnostics) = check(path);
+    let errors: Vec<String> = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .map(|d| d.to_string())
+        .collect();
+    if errors.is_empty() {
+        Ok(exercises)
+    } else {
+        Err(RustlingsError::Manifest {
+            message: errors.join("\n"),
+        })
+    }
+}
+
+// Check the manifest and the files it includes for mistakes, returning the
+// exercises they define (empty if any can't be parsed) and every problem found.
+pub fn check(path: &Path) -> (Vec<Exercise>, Vec<Diagnostic>) {
+    let mut checker = Checker::default();
+    checker.check_file(path, DEFAULT_TRACK.to_string());
+
+    checker.check_requirements();
+
+    for file in glob("exercises/**/*.rs").into_iter().flatten().flatten() {
+        let is_support_file = file
+            .file_name()
+            .is_some_and(|name| SUPPORT_FILES.iter().any(|s| name == *s));
+        let listed = checker.listed.iter().any(|listed| same_file(listed, &file));
+        if !is_support_file && !listed {
+            checker
+                .diagnostics
+                .push(warning(&file, None, "not listed in any manifest".into()));
+        }
+    }
+
+    let has_errors = checker
+        .diagnostics
+        .iter()
+        .any(|d| d.severity == Severity::Error);
+    if has_errors {
+        checker.exercises.clear();
+    }
+    (checker.exercises, checker.diagnostics)
+}
+
+struct Requirement {
+    exercise: String,
+    requires: Vec<String>,
+    file: PathBuf,
+    location: Option<(usize, usize)>,
+}
+
+#[derive(Default)]
+struct Checker {
+    exercises: Vec<Exercise>,
+    diagnostics: Vec<Diagnostic>,
+    // The file and line each exercise name was first defined at
+    names: HashMap<String, (PathBuf, usize)>,
+    // The exercise paths of all manifests
+    listed: Vec<PathBuf>,
+    // The exercise names in the order they are offered in
+    order: Vec<String>,
+    // The prerequisites of each exercise, and where they are listed
+    requirements: Vec<Requirement>,
+    // The manifests checked so far, so each is only read once
+    visited: Vec<PathBuf>,
+}
+
+impl Checker {
+    // Check a single manifest, then the ones it includes. The exercises of
+    // the manifest belong to `track`, unless it names a track of its own.
+    fn check_file(&mut self, path: &Path, track: String) {
+        let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if self.visited.contains(&canonical) {
+            return;
+        }
+        self.visited.push(canonical);
+
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) => {
+                self.diagnostics
+                    .push(error(path, None, format!("can't be read: {e}")));
+                return;
+            }
+        };
+        let value: toml::Value = match toml::from_str(&source) {
+            Ok(value) => value,
+            Err(e) => {
+                self.diagnostics
+                    .push(error(path, line_col(&e), e.to_string()));
+                return;
+            }
+        };
+
+        let errors_before = self.error_count();
+        let entries = value
+            .get("exercises")
+            .and_then(toml::Value::as_array)
+            .map_or(&[][..], Vec::as_slice);
+        let includes = value.get("include").and_then(toml::Value::as_array);
+        if entries.is_empty() && includes.is_none() {
+            self.diagnostics
+                .push(error(path, None, "no `[[exercises]]` found".into()));
+            return;
+        }
+        self.check_entries(path, &source, entries);
+
+        // Only report type errors and the like when the checks above found nothing,
+        // they usually describe the same problem less precisely
+        match toml::from_str::<ExerciseList>(&source) {
+            Ok(list) => {
+                let track = list.track.unwrap_or(track);
+                self.exercises
+                    .extend(list.exercises.into_iter().map(|exercise| Exercise {
+                        track: track.clone(),
+                        ..exercise
+                    }));
+                for pattern in &list.include {
+                    self.include(path, pattern);
+                }
+            }
+            Err(e) => {
+                if self.error_count() == errors_before {
+                    self.diagnostics
+                        .push(error(path, line_col(&e), e.to_string()));
+                }
+            }
+        }
+    }
+
+    // Check the manifests matching an `include` pattern of the manifest at `path`
+    fn include(&mut self, path: &Path, pattern: &str) {
+        let files: Vec<PathBuf> = match glob(pattern) {
+            Ok(files) => files.flatten().collect(),
+            Err(e) => {
+                self.diagnostics.push(error(
+                    path,
+                    None,
+                    format!("invalid `include` pattern `{pattern}`: {e}"),
+                ));
+                return;
+            }
+        };
+        // A plain path has to exist, a pattern may legitimately match nothing
+        if files.is_empty() && Pattern::escape(pattern) == pattern {
+            self.diagnostics.push(error(
+                path,
+                None,
+                format!("included file `{pattern}` doesn't exist"),
+            ));
+        }
+        for file in files {
+            let track = default_track(&file);
+            self.check_file(&file, track);
+        }
+    }
+
+    fn check_entries(&mut self, path: &Path, source: &str, entries: &[toml::Value]) {
+        let locator = Locator::new(source);
+        let marker = Regex::new(I_AM_DONE_REGEX).unwrap();
+        let error_code = Regex::new(r"^E\d{4}$").unwrap();
+        for (index, entry) in entries.iter().enumerate() {
+            let at = |key: &str| locator.key(index, key);
+            let table = match entry.as_table() {
+                Some(table) => table,
+                None => {
+                    self.diagnostics.push(error(
+                        path,
+                        at(""),
+                        "an exercise must be a table".into(),
+                    ));
+                    continue;
+                }
+            };
+            for key in REQUIRED_KEYS {
+                if !table.contains_key(*key) {
+                    self.diagnostics.push(error(
+                        path,
+                        at(""),
+                        format!("exercise is missing `{key}`"),
+                    ));
+                }
+            }
+
+            if let Some(name) = table.get("name").and_then(toml::Value::as_str) {
+                let (line, _) = at("name").unwrap_or_default();
+                match self.names.get(name) {
+                    Some((file, first)) => {
+                        let message = format!(
+                            "duplicate exercise name `{name}`, first defined at {}:{first}",
+                            file.display()
+                        );
+                        self.diagnostics.push(error(path, at("name"), message));
+                    }
+                    None => {
+                        self.names
+                            .insert(name.to_string(), (path.to_path_buf(), line));
+                        self.order.push(name.to_string());
+                    }
+                }
+                if let Some(requires) = table.get("requires").and_then(toml::Value::as_array) {
+                    self.requirements.push(Requirement {
+                        exercise: name.to_string(),
+                        requires: requires
+                            .iter()
+                            .filter_map(toml::Value::as_str)
+                            .map(str::to_string)
+                            .collect(),
+                        file: path.to_path_buf(),
+                        location: at("requires"),
+                    });
+                }
+            }
+
+            if let Some(mode) = table.get("mode") {
+                if let Err(e) = Mode::deserialize(mode.clone()) {
+                    self.diagnostics
+                        .push(error(path, at("mode"), format!("invalid `mode`: {e}")));
+                }
+            }
+
+            if let Some(hint) = table.get("hint") {
+                match Hint::deserialize(hint.clone()) {
+                    Ok(hint) if hint.levels().iter().all(|level| level.trim().is_empty()) => {
+                        self.diagnostics.push(warning(
+                            path,
+                            at("hint"),
+                            "the hint is empty".into(),
+                        ));
+                    }
+                    Ok(hint) if hint.levels().iter().any(|level| level.trim().is_empty()) => {
+                        self.diagnostics.push(warning(
+                            path,
+                            at("hint"),
+                            "one of the hint levels is empty".into(),
+                        ));
+                    }
+                    Ok(_) => {}
+                    Err(_) => {
+                        self.diagnostics.push(error(
+                            path,
+                            at("hint"),
+                            "`hint` has to be a string or a list of strings".into(),
+                        ));
+                    }
+                }
+            }
+
+            if let Some(expected) = table.get("expected_stdout") {
+                match ExpectedStdout::deserialize(expected.clone()) {
+                    Ok(ExpectedStdout::Regex { regex }) => {
+                        if let Err(e) = Regex::new(&regex) {
+                            self.diagnostics.push(error(
+                                path,
+                                at("expected_stdout"),
+                                format!("invalid regex in `expected_stdout`: {e}"),
+                            ));
+                        }
+                    }
+                    Ok(ExpectedStdout::Exact(_)) => {}
+                    Err(_) => {
+                        self.diagnostics.push(error(
+                            path,
+                            at("expected_stdout"),
+                            "`expected_stdout` has to be a string or `{ regex = \"...\" }`".into(),
+                        ));
+                    }
+                }
+            }
+            let mode = table.get("mode").and_then(toml::Value::as_str);
+            if mode == Some("compile_fail") {
+                let codes = table.get("error_codes").and_then(toml::Value::as_array);
+                match codes {
+                    Some(codes) if !codes.is_empty() => {
+                        for code in codes.iter().filter_map(toml::Value::as_str) {
+                            if !error_code.is_match(code) {
+                                self.diagnostics.push(warning(
+                                    path,
+                                    at("error_codes"),
+                                    format!("`{code}` isn't an error code like `E0382`"),
+                                ));
+                            }
+                        }
+                    }
+                    _ => {
+                        self.diagnostics.push(error(
+                            path,
+                            at("error_codes"),
+                            "`compile_fail` exercises need the `error_codes` they are rejected with"
+                                .into(),
+                        ));
+                    }
+                }
+            }
+            if mode == Some("perf") && !table.contains_key("max_duration") {
+                self.diagnostics.push(error(
+                    path,
+                    at("max_duration"),
+                    "`perf` exercises need the `max_duration` their benchmark has to run within"
+                        .into(),
+                ));
+            }
+            for (key, modes) in KEYS_OF_MODES {
+                if table.contains_key(*key) && !mode.is_some_and(|mode| modes.contains(&mode)) {
+                    let modes = modes.join("` or `");
+                    self.diagnostics.push(warning(
+                        path,
+                        at(key),
+                        format!("`{key}` is only used for exercises in `{modes}` mode"),
+                    ));
+                }
+            }
+
+            if let Some(solution) = table.get("solution").and_then(toml::Value::as_str) {
+                self.listed.push(PathBuf::from(solution));
+                if !Path::new(solution).exists() {
+                    self.diagnostics.push(error(
+                        path,
+                        at("solution"),
+                        format!("solution `{solution}` doesn't exist"),
+                    ));
+                }
+            }
+
+            if let Some(exercise_path) = table.get("path").and_then(toml::Value::as_str) {
+                self.listed.push(PathBuf::from(exercise_path));
+                match fs::read_to_string(exercise_path) {
+                    Err(_) if !Path::new(exercise_path).exists() => {
+                        self.diagnostics.push(error(
+                            path,
+                            at("path"),
+                            format!("`{exercise_path}` doesn't exist"),
+                        ));
+                    }
+                    Err(e) => {
+                        self.diagnostics.push(error(
+                            path,
+                            at("path"),
+                            format!("`{exercise_path}` can't be read: {e}"),
+                        ));
+                    }
+                    Ok(source) if !marker.is_match(&source) => {
+                        self.diagnostics.push(warning(
+                            path,
+                            at("path"),
+                            format!("`{exercise_path}` has no `I AM NOT DONE` comment"),
+                        ));
+                    }
+                    Ok(_) => {}
+                }
+            }
+        }
+    }
+
+    // Prerequisites have to exist and come before the exercises requiring them,
+    // otherwise working through the exercises in order would get stuck
+    fn check_requirements(&mut self) {
+        let position = |name: &str| self.order.iter().position(|n| n == name);
+        let mut diagnostics = Vec::new();
+        for requirement in &self.requirements {
+            let own_position = position(&requirement.exercise);
+            for name in &requirement.requires {
+                let message = match position(name) {
+                    None => format!("unknown prerequisite `{name}`"),
+                    Some(p) if Some(p) >= own_position => format!(
+                        "prerequisite `{name}` has to come before `{}`",
+                        requirement.exercise
+                    ),
+                    Some(_) => continue,
+                };
+                diagnostics.push(error(&requirement.file, requirement.location, message));
+            }
+        }
+        self.diagnostics.extend(diagnostics);
+    }
+
+    fn error_count(&self) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+            .count()
+    }
+}
+
+// The track of an included manifest that doesn't name one: the name of its
+// directory, so exercises/algorithm/info.toml defines the `algorithm` track
+fn default_track(path: &Path) -> String {
+    path.parent()
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| DEFAULT_TRACK.to_string())
+}
+
+fn error(file: &Path, location: Option<(usize, usize)>, message: String) -> Diagnostic {
+    Diagnostic {
+        severity: Severity::Error,
+        file: file.to_path_buf(),
+        location,
+        message,
+    }
+}
+
+fn warning(file: &Path, location: Option<(usize, usize)>, message: String) -> Diagnostic {
+    Diagnostic {
+        severity: Severity::Warning,
+        ..error(file, location, message)
+    }
+}
+
+// The 1-based location of a TOML error, the parser counts from 0
+fn line_col(e: &toml::de::Error) -> Option<(usize, usize)> {
+    e.line_col().map(|(line, column)| (line + 1, column + 1))
+}
+
+// Compare paths written in different ways, like `./a/b.rs` and `a/b.rs`
+fn same_file(a: &Path, b: &Path) -> bool {
+    a.components().eq(b.components())
+}
+
+// Finds where the `[[exercises]]` entries and their keys are written,
+// since the parsed TOML values don't remember their position.
+struct Locator<'a> {
+    lines: Vec<&'a str>,
+    // The index of the line of each `[[exercises]]` header
+    entries: Vec<usize>,
+}
+
+impl<'a> Locator<'a> {
+    fn new(source: &'a str) -> Locator<'a> {
+        let lines: Vec<&str> = source.lines().collect();
+        let entries = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.trim() == "[[exercises]]")
+            .map(|(i, _)| i)
+            .collect();
+        Locator { lines, entries }
+    }
+
+    // The location of the key in the given entry, or of the entry itself
+    // if the key isn't there (or is empty)
+    fn key(&self, entry: usize, key: &str) -> Option<(usize, usize)> {
+        let header = *self.entries.get(entry)?;
+        let entry_location = Some((header + 1, 1));
+        if key.is_empty() {
+            return entry_location;
+        }
+        self.lines
+            .iter()
+            .enumerate()
+            .skip(header + 1)
+            .take_while(|(_, line)| !line.trim_start().starts_with('['))
+            .find_map(|(i, line)| {
+                let rest = line.trim_start().strip_prefix(key)?;
+                let value = rest.trim_start().strip_prefix('=')?;
+                let column = line.len() - value.trim_start().len() + 1;
+                Some((i + 1, column))
+            })
+            .or(entry_location)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_locator_finds_keys() {
+        let source = "# comment\n[[exercises]]\nname = \"a\"\n\n[[exercises]]\nname = \"b\"\n  mode = \"test\"\n";
+        let locator = Locator::new(source);
+        assert_eq!(locator.key(0, "name"), Some((3, 8)));
+        assert_eq!(locator.key(1, "mode"), Some((7, 10)));
+        assert_eq!(locator.key(1, "hint"), Some((5, 1)));
+        assert_eq!(locator.key(2, "name"), None);
+    }
+
+    #[test]
+    fn test_default_track_is_the_directory() {
+        assert_eq!(
+            default_track(Path::new("exercises/algorithm/info.toml")),
+            "algorithm"
+        );
+        assert_eq!(default_track(Path::new("info.toml")), DEFAULT_TRACK);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::diff;
+use crate::error::RustlingsError;
+use crate::exercise::{Exercise, Mode};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Where the exercises are copied to before the learner changes them,
+// mirroring their paths
+const PRISTINE_DIR: &str = ".rustlings/pristine";
+
+// The files of the exercise the learner may edit
+fn files(exercise: &Exercise) -> Vec<PathBuf> {
+    let mut files = vec![exercise.path.clone()];
+    if exercise.mode == Mode::BuildScript {
+        files.push(exercise.path.with_file_name("build.rs"));
+    }
+    files
+}
+
+// Where the original of a file of an exercise is kept
+fn original(path: &Path) -> PathBuf {
+    Path::new(PRISTINE_DIR).join(path)
+}
+
+// Copy every file of the exercises that hasn't been copied before.
+// This happens on every start, so the copies are taken before the
+// learner had a chance to change anything. Failing to copy only keeps
+// the exercises from being reset, so it isn't an error.
+pub fn snapshot(exercises: &[Exercise]) {
+    for path in exercises.iter().flat_map(files) {
+        let original = original(&path);
+        if !original.exists() && path.exists() {
+            if let Some(dir) = original.parent() {
+                let _ = fs::create_dir_all(dir).and_then(|_| fs::copy(&path, &original));
+            }
+        }
+    }
+}
+
+// Read the original of a file, failing if there is no copy of it
+fn read_original(path: &Path) -> Result<String, RustlingsError> {
+    fs::read_to_string(original(path))
+        .map_err(|e| RustlingsError::io(format!("There is no original of {}", path.display()), e))
+}
+
+// Whether the files of the exercise differ from their originals
+pub fn is_changed(exercise: &Exercise) -> Result<bool, RustlingsError> {
+    for path in files(exercise) {
+        if fs::read_to_string(&path).ok() != Some(read_original(&path)?) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+// Restore the files of the exercise to their originals
+pub fn reset(exercise: &Exercise) -> Result<(), RustlingsError> {
+    for path in files(exercise) {
+        fs::write(&path, read_original(&path)?)
+            .map_err(|e| RustlingsError::io(format!("Failed to reset {}", path.display()), e))?;
+    }
+    Ok(())
+}
+
+// The changes the learner made to the exercise as a colored unified diff,
+// or an empty string if there are none
+pub fn changes(exercise: &Exercise) -> Result<String, RustlingsError> {
+    let mut changes = String::new();
+    for path in files(exercise) {
+        let current = fs::read_to_string(&path)
+            .map_err(|e| RustlingsError::io(format!("Failed to read {}", path.display()), e))?;
+        let label = path.display().to_string();
+        changes += &diff::unified(
+            &format!("{label} (original)"),
+            &label,
+            &read_original(&path)?,
+            &current,
+        );
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_build_scripts_are_kept_too() {
+        let exercise = Exercise {
+            path: PathBuf::from("exercises/tests/tests7.rs"),
+            mode: Mode::BuildScript,
+            ..Default::default()
+        };
+        assert_eq!(
+            files(&exercise)
+                .iter()
+                .map(|path| original(path))
+                .collect::<Vec<_>>(),
+            [
+                Path::new(".rustlings/pristine/exercises/tests/tests7.rs"),
+                Path::new(".rustlings/pristine/exercises/tests/build.rs")
+            ]
+        );
+    }
+}
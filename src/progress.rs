@@ -0,0 +1,251 @@
+use crate::exercise::{Exercise, Mode};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The directory holding the state rustlings keeps about the workspace
+pub const STATE_DIR: &str = ".rustlings";
+const PROGRESS_PATH: &str = ".rustlings/progress.json";
+
+// The persistent record of which exercises have passed `verify`.
+// An exercise only counts as done while its source still hashes to
+// the value that was recorded when it passed.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Progress {
+    exercises: BTreeMap<String, ExerciseProgress>,
+    // How many hint levels have been revealed per exercise
+    #[serde(default)]
+    hints_used: BTreeMap<String, usize>,
+    // The exercises deferred with `rustlings skip` that haven't passed since
+    #[serde(default)]
+    skipped: BTreeSet<String>,
+}
+
+// What we remember about the last successful verification of an exercise
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExerciseProgress {
+    // Hash of the source file that passed `verify`
+    pub hash: String,
+    // Seconds since the Unix epoch at which it passed
+    pub verified_at: u64,
+    // The mode the exercise was verified in
+    pub mode: Mode,
+}
+
+impl Progress {
+    // Load the progress store of the current workspace.
+    // A missing or unreadable store is treated as no progress at all.
+    pub fn load() -> Progress {
+        fs::read_to_string(PROGRESS_PATH)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    // Write the progress store back to disk.
+    // The file is replaced atomically so a concurrent `load` never sees half of it.
+    pub fn save(&self) -> io::Result<()> {
+        fs::create_dir_all(STATE_DIR)?;
+        let serialized = serde_json::to_string_pretty(self).expect("Failed to serialize progress");
+        let temp_path = format!("{PROGRESS_PATH}.{}", process::id());
+        fs::write(&temp_path, serialized)?;
+        fs::rename(&temp_path, PROGRESS_PATH)
+    }
+
+    // Whether the exercise passed `verify` with its current source and mode
+    pub fn is_verified(&self, exercise: &Exercise) -> bool {
+        match (
+            self.exercises.get(&exercise.name),
+            hash_file(&exercise.path),
+        ) {
+            (Some(entry), Ok(hash)) => entry.hash == hash && entry.mode == exercise.mode,
+            _ => false,
+        }
+    }
+
+    // Whether every prerequisite of the exercise has passed `verify`.
+    // Prerequisites stay met once they passed, even if they were edited since,
+    // so learners aren't locked out of exercises they already started.
+    pub fn is_unlocked(&self, exercise: &Exercise) -> bool {
+        self.missing_prerequisites(exercise).is_empty()
+    }
+
+    // The prerequisites of the exercise that haven't passed `verify` yet
+    pub fn missing_prerequisites<'a>(&self, exercise: &'a Exercise) -> Vec<&'a str> {
+        exercise
+            .requires
+            .iter()
+            .filter(|name| !self.exercises.contains_key(*name))
+            .map(String::as_str)
+            .collect()
+    }
+
+    // How many hint levels of the exercise have been revealed
+    pub fn hints_used(&self, exercise: &Exercise) -> usize {
+        self.hints_used.get(&exercise.name).copied().unwrap_or(0)
+    }
+
+    // Reveal the next hint level of the exercise, if there is one left,
+    // and return how many levels are revealed now
+    pub fn use_hint(&mut self, exercise: &Exercise) -> usize {
+        let levels = exercise.hint.levels().len();
+        let used = self.hints_used.entry(exercise.name.clone()).or_insert(0);
+        *used = (*used + 1).min(levels);
+        *used
+    }
+
+    // Whether the exercise was deferred with `rustlings skip`
+    pub fn is_skipped(&self, exercise: &Exercise) -> bool {
+        self.skipped.contains(&exercise.name)
+    }
+
+    // Defer the exercise until all others are done, or bring it back to its place
+    pub fn set_skipped(&mut self, exercise: &Exercise, skipped: bool) {
+        if skipped {
+            self.skipped.insert(exercise.name.clone());
+        } else {
+            self.skipped.remove(&exercise.name);
+        }
+    }
+
+    // The exercises in the order they are worked on,
+    // which puts the skipped ones after all others
+    pub fn in_order<'a>(&self, exercises: &'a [Exercise]) -> Vec<&'a Exercise> {
+        let (skipped, rest): (Vec<_>, Vec<_>) = exercises.iter().partition(|e| self.is_skipped(e));
+        rest.into_iter().chain(skipped).collect()
+    }
+
+    // Remember that the current source of the exercise passed `verify`.
+    // It isn't deferred anymore then.
+    pub fn record(&mut self, exercise: &Exercise) -> io::Result<()> {
+        let entry = ExerciseProgress {
+            hash: hash_file(&exercise.path)?,
+            verified_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            mode: exercise.mode,
+        };
+        self.exercises.insert(exercise.name.clone(), entry);
+        self.skipped.remove(&exercise.name);
+        Ok(())
+    }
+}
+
+// Record a successful verification in the on-disk progress store
+pub fn record_verified(exercise: &Exercise) -> io::Result<()> {
+    let mut progress = Progress::load();
+    progress.record(exercise)?;
+    progress.save()
+}
+
+// Hash the contents of a file, see `hash_bytes`
+pub fn hash_file(path: &Path) -> io::Result<String> {
+    fs::read(path).map(|bytes| hash_bytes(&bytes))
+}
+
+// A stable 64-bit FNV-1a hash, rendered as hex.
+// Unlike `DefaultHasher` its output doesn't change between Rust releases,
+// so hashes stored on disk stay valid after a toolchain update.
+pub fn hash_bytes(bytes: &[u8]) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let hash = bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    });
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::exercise::Hint;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_hash_is_stable() {
+        assert_eq!(hash_bytes(b""), "cbf29ce484222325");
+        assert_eq!(hash_bytes(b"a"), "af63dc4c8601ec8c");
+    }
+
+    #[test]
+    fn test_record_matches_current_source() {
+        let exercise = Exercise {
+            name: "finished_exercise".into(),
+            path: PathBuf::from("tests/fixture/state/finished_exercise.rs"),
+            mode: Mode::Compile,
+            ..Default::default()
+        };
+        let mut progress = Progress::default();
+        assert!(!progress.is_verified(&exercise));
+        progress.record(&exercise).unwrap();
+        assert!(progress.is_verified(&exercise));
+    }
+
+    #[test]
+    fn test_prerequisites_unlock_exercises() {
+        let prerequisite = Exercise {
+            name: "finished_exercise".into(),
+            path: PathBuf::from("tests/fixture/state/finished_exercise.rs"),
+            ..Default::default()
+        };
+        let exercise = Exercise {
+            name: "next_exercise".into(),
+            requires: vec!["finished_exercise".into()],
+            ..Default::default()
+        };
+        let mut progress = Progress::default();
+        assert!(!progress.is_unlocked(&exercise));
+        assert_eq!(
+            progress.missing_prerequisites(&exercise),
+            ["finished_exercise"]
+        );
+        progress.record(&prerequisite).unwrap();
+        assert!(progress.is_unlocked(&exercise));
+    }
+
+    #[test]
+    fn test_skipped_exercises_come_last() {
+        let exercises: Vec<Exercise> = ["a", "b", "c"]
+            .iter()
+            .map(|name| Exercise {
+                name: name.to_string(),
+                path: PathBuf::from("tests/fixture/state/finished_exercise.rs"),
+                ..Default::default()
+            })
+            .collect();
+        let names = |progress: &Progress| {
+            progress
+                .in_order(&exercises)
+                .iter()
+                .map(|e| e.name.as_str())
+                .collect::<Vec<_>>()
+        };
+        let mut progress = Progress::default();
+        progress.set_skipped(&exercises[0], true);
+        assert_eq!(names(&progress), ["b", "c", "a"]);
+        progress.record(&exercises[0]).unwrap();
+        assert!(!progress.is_skipped(&exercises[0]));
+        assert_eq!(names(&progress), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_hints_are_revealed_one_level_at_a_time() {
+        let exercise = Exercise {
+            name: "hinted".into(),
+            hint: Hint::Levels(vec!["first".into(), "second".into()]),
+            ..Default::default()
+        };
+        let mut progress = Progress::default();
+        assert_eq!(progress.hints_used(&exercise), 0);
+        assert_eq!(progress.use_hint(&exercise), 1);
+        assert_eq!(progress.use_hint(&exercise), 2);
+        assert_eq!(progress.use_hint(&exercise), 2);
+        assert_eq!(progress.hints_used(&exercise), 2);
+    }
+}
@@ -1,57 +1,61 @@
-use std::process::Command;
-
+use crate::error::RustlingsError;
 use crate::exercise::{Exercise, Mode};
-use crate::verify::test;
+use crate::verify::{compile, test, warn_limit_exceeded, warn_mismatch};
 use indicatif::ProgressBar;
 
 // Invoke the rust compiler on the path of the given exercise,
 // and run the ensuing binary.
 // The verbose argument helps determine whether or not to show
-// the output from the test harnesses (if the mode of the exercise is test)
-pub fn run(exercise: &Exercise, verbose: bool) -> Result<(), ()> {
+// the output from the test harnesses (if the mode of the exercise is test),
+// the filter selects the tests to run
+pub fn run(exercise: &Exercise, verbose: bool, filter: Option<&str>) -> Result<(), RustlingsError> {
     match exercise.mode {
-        Mode::Test => test(exercise, verbose)?,
+        Mode::Test | Mode::Doctest | Mode::Perf => test(exercise, verbose, filter)?,
         Mode::Compile => compile_and_run(exercise)?,
         Mode::Clippy => compile_and_run(exercise)?,
-        Mode::BuildScript => test(exercise, verbose)?,
+        Mode::BuildScript => test(exercise, verbose, None)?,
+        Mode::CompileFail => compile_expecting_errors(exercise)?,
+        Mode::Fmt => compile_and_check_formatting(exercise)?,
     }
     Ok(())
 }
 
-// Resets the exercise by stashing the changes.
-pub fn reset(exercise: &Exercise) -> Result<(), ()> {
-    let command = Command::new("git")
-        .args(["stash", "--"])
-        .arg(&exercise.path)
-        .spawn();
+// Invoke the rust compiler on the path of the given exercise,
+// which has to reject it, and show the errors it rejects it with
+fn compile_expecting_errors(exercise: &Exercise) -> Result<(), RustlingsError> {
+    let progress_bar = ProgressBar::new_spinner();
+    progress_bar.set_message(format!("Compiling {exercise}..."));
+    progress_bar.enable_steady_tick(100);
 
-    match command {
-        Ok(_) => Ok(()),
-        Err(_) => Err(()),
-    }
+    let compilation = compile(exercise, &progress_bar)?;
+    progress_bar.finish_and_clear();
+    println!("{}", compilation.output.stderr);
+    success!("{} is rejected as expected", exercise);
+    Ok(())
+}
+
+// Invoke the rust compiler on the path of the given exercise,
+// and check that it is formatted the way rustfmt formats it
+fn compile_and_check_formatting(exercise: &Exercise) -> Result<(), RustlingsError> {
+    let progress_bar = ProgressBar::new_spinner();
+    progress_bar.set_message(format!("Compiling {exercise}..."));
+    progress_bar.enable_steady_tick(100);
+
+    compile(exercise, &progress_bar)?;
+    progress_bar.finish_and_clear();
+    success!("{} is compiling and formatted", exercise);
+    Ok(())
 }
 
 // Invoke the rust compiler on the path of the given exercise
 // and run the ensuing binary.
 // This is strictly for non-test binaries, so output is displayed
-fn compile_and_run(exercise: &Exercise) -> Result<(), ()> {
+fn compile_and_run(exercise: &Exercise) -> Result<(), RustlingsError> {
     let progress_bar = ProgressBar::new_spinner();
     progress_bar.set_message(format!("Compiling {exercise}..."));
     progress_bar.enable_steady_tick(100);
 
-    let compilation_result = exercise.compile();
-    let compilation = match compilation_result {
-        Ok(compilation) => compilation,
-        Err(output) => {
-            progress_bar.finish_and_clear();
-            warn!(
-                "Compilation of {} failed!, Compiler error message:\n",
-                exercise
-            );
-            println!("{}", output.stderr);
-            return Err(());
-        }
-    };
+    let compilation = compile(exercise, &progress_bar)?;
 
     progress_bar.set_message(format!("Running {exercise}..."));
     let result = compilation.run();
@@ -63,12 +67,16 @@ fn compile_and_run(exercise: &Exercise) -> Result<(), ()> {
             success!("Successfully ran {}", exercise);
             Ok(())
         }
-        Err(output) => {
-            println!("{}", output.stdout);
-            println!("{}", output.stderr);
+        Err(err) => {
+            if let Some(output) = err.output() {
+                println!("{}", output.stdout);
+                println!("{}", output.stderr);
 
-            warn!("Ran {} with errors", exercise);
-            Err(())
+                warn!("Ran {} with errors", exercise);
+                warn_limit_exceeded(exercise, output);
+                warn_mismatch(exercise, output);
+            }
+            Err(err)
         }
     }
 }
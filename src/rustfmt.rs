@@ -0,0 +1,84 @@
+use crate::error::RustlingsError;
+use crate::exercise::{Exercise, ExerciseOutput};
+use console::style;
+use std::process::Command;
+
+// The diff is colored by us, the same way in and outside of terminals
+const RUSTFMT_ARGS: &[&str] = &["--edition", "2021", "--color", "never"];
+
+fn run(exercise: &Exercise, check: bool) -> Result<ExerciseOutput, RustlingsError> {
+    let mut command = Command::new("rustfmt");
+    if check {
+        command.arg("--check");
+    }
+    let output = command
+        .args(RUSTFMT_ARGS)
+        .arg(&exercise.path)
+        .output()
+        .map_err(|e| RustlingsError::spawn("rustfmt", e))?;
+    Ok(ExerciseOutput::from_output(&output))
+}
+
+// Check that the exercise is formatted the way rustfmt formats it.
+// If it isn't, the output holds what rustfmt would change.
+pub fn check(exercise: &Exercise) -> Result<(), RustlingsError> {
+    let mut output = run(exercise, true)?;
+    if output.status == Some(0) {
+        return Ok(());
+    }
+    output.stdout = highlight(&output.stdout);
+    Err(RustlingsError::Unformatted {
+        exercise: exercise.name.clone(),
+        output,
+    })
+}
+
+// Format the exercise in place. rustfmt only fails on code it can't parse.
+pub fn apply(exercise: &Exercise) -> Result<(), RustlingsError> {
+    let output = run(exercise, false)?;
+    if output.status == Some(0) {
+        Ok(())
+    } else {
+        Err(RustlingsError::CompileFailure {
+            exercise: exercise.name.clone(),
+            output,
+        })
+    }
+}
+
+// Color the lines rustfmt would remove and add
+fn highlight(diff: &str) -> String {
+    diff.lines()
+        .map(|line| {
+            let line = if line.starts_with("Diff in ") {
+                style(line).bold()
+            } else if line.starts_with('-') {
+                style(line).red()
+            } else if line.starts_with('+') {
+                style(line).green()
+            } else {
+                style(line)
+            };
+            format!("{line}\n")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_check_shows_the_changes() {
+        let exercise = Exercise {
+            name: "unformatted".into(),
+            path: PathBuf::from("tests/fixture/fmt/unformatted.rs"),
+            ..Default::default()
+        };
+        let err = check(&exercise).unwrap_err();
+        let diff = console::strip_ansi_codes(&err.output().unwrap().stdout).to_string();
+        assert!(diff.contains("-fn main(){"));
+        assert!(diff.contains("+fn main() {"));
+    }
+}
@@ -0,0 +1,160 @@
+use console::{Key, Term};
+use std::io::{self, IsTerminal};
+
+// The commands of the watch shell that take the name of an exercise
+const TAKES_EXERCISE: &[&str] = &["goto", "focus", "skip"];
+
+// Reads the commands of the watch shell. On a terminal lines can be edited,
+// earlier lines are recalled with the arrow keys, and commands and exercise
+// names are completed with Tab.
+pub struct LineEditor {
+    commands: Vec<String>,
+    names: Vec<String>,
+    history: Vec<String>,
+}
+
+impl LineEditor {
+    pub fn new(commands: &[&str], names: Vec<String>) -> LineEditor {
+        LineEditor {
+            commands: commands.iter().map(|c| c.to_string()).collect(),
+            names,
+            history: Vec::new(),
+        }
+    }
+
+    // Read the next line, `None` once the input ended
+    pub fn read_line(&mut self) -> io::Result<Option<String>> {
+        if !io::stdin().is_terminal() {
+            let mut line = String::new();
+            return match io::stdin().read_line(&mut line)? {
+                0 => Ok(None),
+                _ => Ok(Some(line.trim_end().to_string())),
+            };
+        }
+
+        let term = Term::stdout();
+        let mut line: Vec<char> = Vec::new();
+        let mut cursor = 0;
+        // The entry of the history shown, the line being typed comes after them
+        let mut recalled = self.history.len();
+        loop {
+            match term.read_key()? {
+                Key::Enter => {
+                    term.write_line("")?;
+                    break;
+                }
+                // Ctrl-D
+                Key::Char('\x04') if line.is_empty() => {
+                    term.write_line("")?;
+                    return Ok(None);
+                }
+                Key::Char(c) if !c.is_control() => {
+                    line.insert(cursor, c);
+                    cursor += 1;
+                }
+                Key::Backspace if cursor > 0 => {
+                    cursor -= 1;
+                    line.remove(cursor);
+                }
+                Key::Del if cursor < line.len() => {
+                    line.remove(cursor);
+                }
+                Key::ArrowLeft => cursor = cursor.saturating_sub(1),
+                Key::ArrowRight => cursor = (cursor + 1).min(line.len()),
+                Key::Home => cursor = 0,
+                Key::End => cursor = line.len(),
+                Key::ArrowUp if recalled > 0 => {
+                    recalled -= 1;
+                    line = self.history[recalled].chars().collect();
+                    cursor = line.len();
+                }
+                Key::ArrowDown if recalled < self.history.len() => {
+                    recalled += 1;
+                    line = self
+                        .history
+                        .get(recalled)
+                        .map(|l| l.chars().collect())
+                        .unwrap_or_default();
+                    cursor = line.len();
+                }
+                Key::Tab => {
+                    let typed: String = line.iter().collect();
+                    let (completed, candidates) = self.complete(&typed);
+                    if candidates.len() > 1 && completed == typed {
+                        term.write_line("")?;
+                        term.write_line(&candidates.join("  "))?;
+                    }
+                    line = completed.chars().collect();
+                    cursor = line.len();
+                }
+                _ => {}
+            }
+            let shown: String = line.iter().collect();
+            term.clear_line()?;
+            term.write_str(&shown)?;
+            term.move_cursor_left(line.len() - cursor)?;
+        }
+
+        let line: String = line.into_iter().collect();
+        if !line.trim().is_empty() && self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+        }
+        Ok(Some(line))
+    }
+
+    // Complete the last word of the line as far as all candidates for it agree,
+    // returning the line and the candidates
+    fn complete(&self, line: &str) -> (String, Vec<&str>) {
+        let (start, word) = match line.rsplit_once(' ') {
+            Some((start, word)) => (format!("{start} "), word),
+            None => (String::new(), line),
+        };
+        let words = match start.split_whitespace().collect::<Vec<_>>()[..] {
+            [] => &self.commands,
+            [command] if TAKES_EXERCISE.contains(&command) => &self.names,
+            _ => return (line.to_string(), Vec::new()),
+        };
+        let candidates: Vec<&str> = words
+            .iter()
+            .filter(|candidate| candidate.starts_with(word))
+            .map(String::as_str)
+            .collect();
+        let completed = match candidates[..] {
+            [] => word.to_string(),
+            [candidate] => format!("{candidate} "),
+            _ => common_prefix(&candidates).to_string(),
+        };
+        (start + &completed, candidates)
+    }
+}
+
+fn common_prefix<'a>(words: &[&'a str]) -> &'a str {
+    let first = words[0];
+    let end = first
+        .char_indices()
+        .map(|(i, c)| i + c.len_utf8())
+        .take_while(|&end| words.iter().all(|word| word.starts_with(&first[..end])))
+        .last()
+        .unwrap_or(0);
+    &first[..end]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_complete_commands_and_names() {
+        let editor = LineEditor::new(
+            &["goto", "hint", "help"],
+            vec!["vecs1".into(), "vecs2".into(), "variables1".into()],
+        );
+        assert_eq!(editor.complete("go").0, "goto ");
+        assert_eq!(editor.complete("h").0, "h");
+        assert_eq!(editor.complete("he").0, "help ");
+        assert_eq!(editor.complete("goto v").0, "goto v");
+        assert_eq!(editor.complete("goto ve").0, "goto vecs");
+        assert_eq!(editor.complete("goto vecs").1, ["vecs1", "vecs2"]);
+        assert_eq!(editor.complete("hint v").0, "hint v");
+    }
+}
@@ -0,0 +1,453 @@
+use crate::cache;
+use crate::error::RustlingsError;
+use crate::exercise::{ContextLine, Exercise, State};
+use crate::history;
+use crate::pristine;
+use crate::progress::{self, Progress};
+use crate::watcher::{self, WatchOptions};
+use crate::WatchStatus;
+use console::{pad_str, style, Alignment, Key, Term};
+use std::env;
+use std::io;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+// The width of the exercise list on the left
+const LIST_WIDTH: usize = 24;
+// The number of lines of the marker context pane
+const CONTEXT_HEIGHT: usize = 5;
+// The number of lines of the hint pane
+const HINT_HEIGHT: usize = 6;
+// How many lines PageUp and PageDown scroll the output
+const PAGE: usize = 10;
+
+const KEYS: &str = " h hint   n next   r reset   ↑↓/PgUp/PgDn scroll   q quit";
+
+// Whether the terminal can show the full-screen interface
+pub fn is_supported() -> bool {
+    Term::stdout().is_term() && env::var("TERM").map_or(true, |term| term != "dumb")
+}
+
+enum Event {
+    Key(Key),
+    Changed(PathBuf),
+}
+
+// Watch the exercises like the line-based watch mode does, showing the
+// exercises, the output, the marker context and hints in panes of a
+// full-screen interface that is controlled with single keys.
+pub fn watch(exercises: &[Exercise], options: WatchOptions) -> notify::Result<WatchStatus> {
+    let (tx, rx) = channel();
+    forward_changes(watcher::changed_files(options)?, tx.clone());
+    read_keys(tx);
+
+    let term = Term::stdout();
+    let _screen = Screen::enter(&term)?;
+    let mut app = App::new(exercises);
+    app.message = "Checking your progress...".to_string();
+    app.draw(&term)?;
+    app.check_pending(app.current);
+
+    loop {
+        if app.is_finished() {
+            return Ok(WatchStatus::Finished);
+        }
+        app.draw(&term)?;
+        match rx.recv() {
+            Ok(Event::Key(Key::Char('q'))) | Ok(Event::Key(Key::Escape)) | Err(_) => {
+                return Ok(WatchStatus::Unfinished)
+            }
+            Ok(Event::Key(Key::Char('h'))) => app.reveal_hint(),
+            Ok(Event::Key(Key::Char('n'))) => {
+                let next = app.next_pending();
+                app.check(next);
+            }
+            Ok(Event::Key(Key::Char('r'))) => {
+                let exercise = &exercises[app.current];
+                app.message = match pristine::reset(exercise) {
+                    Ok(()) => format!("Reset {}", exercise.name),
+                    Err(e) => e.to_string(),
+                };
+            }
+            Ok(Event::Key(Key::ArrowUp)) | Ok(Event::Key(Key::Char('k'))) => app.scroll_up(1),
+            Ok(Event::Key(Key::ArrowDown)) | Ok(Event::Key(Key::Char('j'))) => app.scroll_down(1),
+            Ok(Event::Key(Key::PageUp)) => app.scroll_up(PAGE),
+            Ok(Event::Key(Key::PageDown)) => app.scroll_down(PAGE),
+            Ok(Event::Key(_)) => {}
+            Ok(Event::Changed(path)) => {
+                let changed = exercises.iter().position(|e| path.ends_with(&e.path));
+                if let Some(index) = changed {
+                    app.message = format!("Checking {}...", exercises[index].name);
+                    app.draw(&term)?;
+                    app.check_pending(index);
+                }
+            }
+        }
+    }
+}
+
+// Pass the changed exercise files on as events
+fn forward_changes(changes: Receiver<PathBuf>, tx: Sender<Event>) {
+    thread::spawn(move || {
+        for path in changes {
+            if tx.send(Event::Changed(path)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+fn read_keys(tx: Sender<Event>) {
+    thread::spawn(move || {
+        let term = Term::stdout();
+        while let Ok(key) = term.read_key() {
+            if tx.send(Event::Key(key)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+// Switches to the alternate screen while the interface is shown,
+// and back to the normal one when dropped
+struct Screen<'a> {
+    term: &'a Term,
+}
+
+impl<'a> Screen<'a> {
+    fn enter(term: &'a Term) -> io::Result<Screen<'a>> {
+        term.write_str("\x1b[?1049h")?;
+        term.hide_cursor()?;
+        Ok(Screen { term })
+    }
+}
+
+impl Drop for Screen<'_> {
+    fn drop(&mut self) {
+        let _ = self.term.show_cursor();
+        let _ = self.term.write_str("\x1b[?1049l");
+    }
+}
+
+struct App<'a> {
+    exercises: &'a [Exercise],
+    progress: Progress,
+    // The exercise the panes show
+    current: usize,
+    // The output of the last check of the current exercise
+    output: Vec<String>,
+    // The first line of the output that is shown
+    scroll: usize,
+    // The lines around the marker, if the exercise passes but isn't done
+    context: Vec<ContextLine>,
+    // The outcome of the last action
+    message: String,
+}
+
+impl<'a> App<'a> {
+    fn new(exercises: &'a [Exercise]) -> App<'a> {
+        let mut app = App {
+            exercises,
+            progress: Progress::load(),
+            current: 0,
+            output: Vec::new(),
+            scroll: 0,
+            context: Vec::new(),
+            message: String::new(),
+        };
+        app.current = app.next_pending_from(0).unwrap_or(0);
+        app
+    }
+
+    fn is_finished(&self) -> bool {
+        self.exercises.iter().all(|e| self.progress.is_verified(e))
+    }
+
+    // The first exercise from `start` on that is unlocked but not verified yet.
+    // Skipped exercises only come up once no other is left.
+    fn next_pending_from(&self, start: usize) -> Option<usize> {
+        let len = self.exercises.len();
+        let pending = (start..len).chain(0..start).filter(|&i| {
+            let exercise = &self.exercises[i];
+            !self.progress.is_verified(exercise) && self.progress.is_unlocked(exercise)
+        });
+        let (skipped, rest): (Vec<_>, Vec<_>) =
+            pending.partition(|&i| self.progress.is_skipped(&self.exercises[i]));
+        rest.into_iter().chain(skipped).next()
+    }
+
+    // The pending exercise after the current one, or the current one if
+    // there is no other
+    fn next_pending(&self) -> usize {
+        self.next_pending_from(self.current + 1)
+            .unwrap_or(self.current)
+    }
+
+    // Check the exercise at `index`, then every pending exercise until
+    // one isn't done, like the line-based watch mode does
+    fn check_pending(&mut self, index: usize) {
+        if !self.check(index) {
+            return;
+        }
+        while let Some(next) = self.next_pending_from(self.current) {
+            if !self.check(next) {
+                return;
+            }
+        }
+    }
+
+    // Make the exercise at `index` the current one and check it,
+    // returning whether it is done
+    fn check(&mut self, index: usize) -> bool {
+        let exercise = &self.exercises[index];
+        self.current = index;
+        self.output.clear();
+        self.scroll = 0;
+        self.context.clear();
+
+        let missing = self.progress.missing_prerequisites(exercise);
+        if !missing.is_empty() {
+            self.message = format!(
+                "{} is locked, verify these exercises first: {}",
+                exercise.name,
+                missing.join(", ")
+            );
+            return false;
+        }
+
+        let result = match cache::compile_and_run(exercise) {
+            Err(err) => {
+                self.message = err.to_string();
+                if let Some(output) = err.output() {
+                    self.add_output(&output.stdout);
+                    self.add_output(&output.stderr);
+                    if let Some(limit) = output.limit_exceeded {
+                        self.output.push(format!("Stopped: {limit}"));
+                    }
+                    if let Some(diff) = exercise.mismatch(output).and_then(|m| m.diff()) {
+                        self.add_output(&diff);
+                    }
+                }
+                Err(err)
+            }
+            Ok(output) => {
+                self.add_output(&output.stdout);
+                match exercise.state() {
+                    State::Done => {
+                        if let Err(e) = progress::record_verified(exercise) {
+                            self.message = format!("Failed to record your progress: {e}");
+                        } else {
+                            self.message = format!("{} is done!", exercise.name);
+                        }
+                        self.progress = Progress::load();
+                        Ok(())
+                    }
+                    State::Pending(context) => {
+                        self.context = context;
+                        self.message = format!(
+                            "{} passes! Remove the `I AM NOT DONE` comment to move on",
+                            exercise.name
+                        );
+                        Err(RustlingsError::Pending {
+                            exercise: exercise.name.clone(),
+                        })
+                    }
+                }
+            }
+        };
+        // Failures of the environment say nothing about the code
+        if !matches!(&result, Err(e) if e.exercise().is_none()) {
+            if let Err(e) = history::record(exercise, result.as_ref().map(|_| ())) {
+                self.message = format!("Failed to save the history of {}: {e}", exercise.name);
+            }
+        }
+        result.is_ok()
+    }
+
+    // Add the text to the output, without the blank lines it starts with
+    fn add_output(&mut self, text: &str) {
+        let lines = text
+            .lines()
+            .skip_while(|line| line.trim().is_empty())
+            .map(|line| line.replace('\t', "    "));
+        self.output.extend(lines);
+    }
+
+    fn reveal_hint(&mut self) {
+        let exercise = &self.exercises[self.current];
+        self.progress.use_hint(exercise);
+        if let Err(e) = self.progress.save() {
+            self.message = format!("Failed to record your progress: {e}");
+        }
+    }
+
+    fn scroll_up(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    fn scroll_down(&mut self, lines: usize) {
+        self.scroll = (self.scroll + lines).min(self.output.len().saturating_sub(1));
+    }
+
+    fn draw(&self, term: &Term) -> io::Result<()> {
+        let (rows, columns) = term.size();
+        let frame = self.render(columns as usize, rows as usize).join("\r\n");
+        term.write_str("\x1b[H")?;
+        term.write_str(&frame)?;
+        term.flush()
+    }
+
+    // The lines of the whole screen, each exactly `width` columns wide
+    fn render(&self, width: usize, height: usize) -> Vec<String> {
+        let body = height.saturating_sub(1);
+        let right_width = width.saturating_sub(LIST_WIDTH + 1);
+        let list = self.list_pane(body);
+        let right = self.right_panes(body);
+        let separator = style("│").dim().to_string();
+        let mut lines: Vec<String> = (0..body)
+            .map(|i| {
+                format!(
+                    "{}{separator}{}",
+                    cell(&list[i], LIST_WIDTH),
+                    cell(&right[i], right_width)
+                )
+            })
+            .collect();
+        if height > 0 {
+            lines.push(style(cell(KEYS, width)).reverse().to_string());
+        }
+        lines
+    }
+
+    // The exercise list, scrolled so the current exercise is visible
+    fn list_pane(&self, height: usize) -> Vec<String> {
+        let mut lines = vec![style("Exercises").bold().to_string()];
+        let rows = height.saturating_sub(1);
+        let offset = self
+            .current
+            .saturating_sub(rows / 2)
+            .min(self.exercises.len().saturating_sub(rows));
+        for (i, exercise) in self.exercises.iter().enumerate().skip(offset).take(rows) {
+            let status = if self.progress.is_verified(exercise) {
+                style("✓").green()
+            } else if self.progress.is_skipped(exercise) {
+                style("»").dim()
+            } else if self.progress.is_unlocked(exercise) {
+                style("•").yellow()
+            } else {
+                style("-").dim()
+            };
+            let name = if i == self.current {
+                style(cell(&exercise.name, LIST_WIDTH - 4)).reverse()
+            } else {
+                style(exercise.name.clone())
+            };
+            lines.push(format!(" {status} {name}"));
+        }
+        fill(lines, height)
+    }
+
+    // The current exercise with its output, marker context and hints
+    fn right_panes(&self, height: usize) -> Vec<String> {
+        let exercise = &self.exercises[self.current];
+        let mut lines = vec![
+            format!(
+                "{} {}",
+                style(&exercise.name).bold(),
+                style(format!("({})", exercise.path.display())).dim()
+            ),
+            self.message.clone(),
+        ];
+
+        let output_height = height.saturating_sub(lines.len() + 3 + CONTEXT_HEIGHT + HINT_HEIGHT);
+        lines.push(title(&format!(
+            "Output ({}/{})",
+            (self.scroll + 1).min(self.output.len()),
+            self.output.len()
+        )));
+        lines.extend(
+            fill(
+                self.output.iter().skip(self.scroll).cloned().collect(),
+                output_height,
+            )
+            .into_iter()
+            .take(output_height),
+        );
+
+        lines.push(title("Context"));
+        let context = self.context.iter().map(|context_line| {
+            let text = if context_line.important {
+                style(&context_line.line).bold().to_string()
+            } else {
+                context_line.line.clone()
+            };
+            format!(
+                "{:>3} {} {text}",
+                style(context_line.number).blue().bold(),
+                style("|").blue()
+            )
+        });
+        lines.extend(fill(context.take(CONTEXT_HEIGHT).collect(), CONTEXT_HEIGHT));
+
+        let levels = exercise.hint.levels();
+        let used = self.progress.hints_used(exercise);
+        lines.push(title(&format!("Hint ({used} of {})", levels.len())));
+        let hints: Vec<String> = levels[..used]
+            .iter()
+            .flat_map(|level| level.trim().lines())
+            .map(str::to_string)
+            .collect();
+        // The latest level is the most useful one, so keep the end in view
+        let skipped = hints.len().saturating_sub(HINT_HEIGHT);
+        lines.extend(fill(hints[skipped..].to_vec(), HINT_HEIGHT));
+
+        fill(lines, height)
+    }
+}
+
+fn title(text: &str) -> String {
+    style(format!("── {text} ")).cyan().to_string()
+}
+
+// Pad or truncate text to exactly `width` columns, ignoring color codes
+fn cell(text: &str, width: usize) -> String {
+    pad_str(text, width, Alignment::Left, Some("…")).into_owned()
+}
+
+// Pad the lines with empty ones, or cut them off, to exactly `height` lines
+fn fill(mut lines: Vec<String>, height: usize) -> Vec<String> {
+    lines.resize(height, String::new());
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use console::measure_text_width;
+
+    #[test]
+    fn test_render_fills_the_screen() {
+        let exercises = vec![
+            Exercise {
+                name: "first".into(),
+                path: PathBuf::from("tests/fixture/state/pending_exercise.rs"),
+                ..Default::default()
+            },
+            Exercise {
+                name: "second".into(),
+                path: PathBuf::from("tests/fixture/state/finished_exercise.rs"),
+                ..Default::default()
+            },
+        ];
+        let mut app = App::new(&exercises);
+        app.output = (0..100).map(|i| format!("line {i}")).collect();
+        app.message = "a very long message ".repeat(20);
+
+        let lines = app.render(80, 30);
+        assert_eq!(lines.len(), 30);
+        for line in &lines {
+            assert_eq!(measure_text_width(line), 80);
+        }
+    }
+}
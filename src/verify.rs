@@ -1,4 +1,8 @@
-use crate::exercise::{CompiledExercise, Exercise, Mode, State};
+use crate::cache;
+use crate::error::RustlingsError;
+use crate::exercise::{CompiledExercise, Exercise, ExerciseOutput, Mode, State};
+use crate::libtest;
+use crate::progress;
 use console::style;
 use indicatif::{ProgressBar, ProgressStyle};
 use std::env;
@@ -6,6 +10,7 @@ use std::env;
 // Verify that the provided container of Exercise objects
 // can be compiled and run without any failures.
 // Any such failures will be reported to the end user.
+// Exercises that pass are recorded in the progress store.
 // If the Exercise being verified is a test, the verbose boolean
 // determines whether or not the test harness outputs are displayed.
 pub fn verify<'a>(
@@ -13,7 +18,7 @@ pub fn verify<'a>(
     progress: (usize, usize),
     verbose: bool,
     success_hints: bool,
-) -> Result<(), &'a Exercise> {
+) -> Result<(), RustlingsError> {
     let (num_done, total) = progress;
     let bar = ProgressBar::new(total as u64);
     let mut percentage = num_done as f32 / total as f32 * 100.0;
@@ -25,15 +30,28 @@ pub fn verify<'a>(
     bar.set_message(format!("({:.1} %)", percentage));
 
     for exercise in exercises {
-        let compile_result = match exercise.mode {
-            Mode::Test => compile_and_test(exercise, RunMode::Interactive, verbose, success_hints),
-            Mode::Compile => compile_and_run_interactively(exercise, success_hints),
-            Mode::Clippy => compile_only(exercise, success_hints),
-            Mode::BuildScript => compile_and_test(exercise, RunMode::Interactive, verbose, success_hints),
-
+        let compile_result = match cache::lookup(exercise) {
+            Some(output) => Ok(cached(exercise, output, verbose, success_hints)),
+            None => match exercise.mode {
+                Mode::Test | Mode::Perf => {
+                    compile_and_test(exercise, RunMode::Interactive, verbose, success_hints, None)
+                }
+                Mode::Compile => compile_and_run_interactively(exercise, success_hints),
+                Mode::Clippy | Mode::CompileFail | Mode::Fmt => {
+                    compile_only(exercise, success_hints)
+                }
+                Mode::BuildScript | Mode::Doctest => {
+                    compile_and_test(exercise, RunMode::Interactive, verbose, success_hints, None)
+                }
+            },
         };
-        if !compile_result.unwrap_or(false) {
-            return Err(exercise);
+        if !compile_result? {
+            return Err(RustlingsError::Pending {
+                exercise: exercise.name.clone(),
+            });
+        }
+        if let Err(e) = progress::record_verified(exercise) {
+            warn!("Failed to record your progress: {}", e);
         }
         percentage += 100.0 / total as f32;
         bar.inc(1);
@@ -42,31 +60,98 @@ pub fn verify<'a>(
     Ok(())
 }
 
+// Check that the reference solution of every exercise that has one
+// passes, the same way the exercise itself would be checked.
+// All solutions are checked, the first failure is returned.
+pub fn verify_solutions(exercises: &[Exercise]) -> Result<(), RustlingsError> {
+    let mut first_failure = None;
+    let mut checked = 0;
+    for exercise in exercises {
+        let solution = match &exercise.solution {
+            Some(path) => Exercise {
+                path: path.clone(),
+                ..exercise.clone()
+            },
+            None => continue,
+        };
+        checked += 1;
+        match cache::compile_and_run(&solution) {
+            Ok(_) => success!("The solution of {} passes", exercise.name),
+            Err(err) => {
+                warn!("The solution of {} fails", exercise.name);
+                if let Some(output) = err.output() {
+                    println!("{}", output.stdout);
+                    println!("{}", output.stderr);
+                } else {
+                    println!("{err}");
+                }
+                first_failure.get_or_insert(err);
+            }
+        }
+    }
+    println!(
+        "Checked {checked} solutions, {} exercises have none.",
+        exercises.len() - checked
+    );
+    match first_failure {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
 enum RunMode {
     Interactive,
     NonInteractive,
 }
 
-// Compile and run the resulting test harness of the given Exercise
-pub fn test(exercise: &Exercise, verbose: bool) -> Result<(), ()> {
-    compile_and_test(exercise, RunMode::NonInteractive, verbose, false)?;
+// Compile and run the resulting test harness of the given Exercise,
+// only running the tests whose names contain the filter if there is one
+pub fn test(
+    exercise: &Exercise,
+    verbose: bool,
+    filter: Option<&str>,
+) -> Result<(), RustlingsError> {
+    compile_and_test(exercise, RunMode::NonInteractive, verbose, false, filter)?;
     Ok(())
 }
 
-// Invoke the rust compiler without running the resulting binary
-fn compile_only(exercise: &Exercise, success_hints: bool) -> Result<bool, ()> {
+// An exercise that passed with the same inputs before isn't compiled
+// again, the output it passed with is shown instead
+fn cached(exercise: &Exercise, output: ExerciseOutput, verbose: bool, success_hints: bool) -> bool {
+    match exercise.mode {
+        Mode::Compile => prompt_for_completion(exercise, Some(output.stdout), success_hints),
+        Mode::Test | Mode::BuildScript | Mode::Doctest => {
+            if verbose {
+                println!("{}", output.stdout);
+            }
+            prompt_for_completion(exercise, None, success_hints)
+        }
+        Mode::Clippy | Mode::Fmt => prompt_for_completion(exercise, None, success_hints),
+        Mode::Perf => prompt_for_completion(exercise, Some(output.stdout), success_hints),
+        Mode::CompileFail => prompt_for_completion(exercise, Some(output.stderr), success_hints),
+    }
+}
+
+// Invoke the rust compiler without running the resulting binary.
+// The errors a compile_fail exercise is rejected with are shown.
+fn compile_only(exercise: &Exercise, success_hints: bool) -> Result<bool, RustlingsError> {
     let progress_bar = ProgressBar::new_spinner();
     progress_bar.set_message(format!("Compiling {exercise}..."));
     progress_bar.enable_steady_tick(100);
 
-    let _ = compile(exercise, &progress_bar)?;
+    let compilation = compile(exercise, &progress_bar)?;
     progress_bar.finish_and_clear();
+    cache::store(exercise, &compilation.output);
 
-    Ok(prompt_for_completion(exercise, None, success_hints))
+    let errors = (exercise.mode == Mode::CompileFail).then(|| compilation.output.stderr.clone());
+    Ok(prompt_for_completion(exercise, errors, success_hints))
 }
 
 // Compile the given Exercise and run the resulting binary in an interactive mode
-fn compile_and_run_interactively(exercise: &Exercise, success_hints: bool) -> Result<bool, ()> {
+fn compile_and_run_interactively(
+    exercise: &Exercise,
+    success_hints: bool,
+) -> Result<bool, RustlingsError> {
     let progress_bar = ProgressBar::new_spinner();
     progress_bar.set_message(format!("Compiling {exercise}..."));
     progress_bar.enable_steady_tick(100);
@@ -78,12 +163,19 @@ fn compile_and_run_interactively(exercise: &Exercise, success_hints: bool) -> Re
     progress_bar.finish_and_clear();
 
     let output = match result {
-        Ok(output) => output,
-        Err(output) => {
-            warn!("Ran {} with errors", exercise);
-            println!("{}", output.stdout);
-            println!("{}", output.stderr);
-            return Err(());
+        Ok(output) => {
+            cache::store(exercise, &output);
+            output
+        }
+        Err(err) => {
+            if let Some(output) = err.output() {
+                warn!("Ran {} with errors", exercise);
+                println!("{}", output.stdout);
+                println!("{}", output.stderr);
+                warn_limit_exceeded(exercise, output);
+                warn_mismatch(exercise, output);
+            }
+            return Err(err);
         }
     };
 
@@ -91,56 +183,134 @@ fn compile_and_run_interactively(exercise: &Exercise, success_hints: bool) -> Re
 }
 
 // Compile the given Exercise as a test harness and display
-// the output if verbose is set to true
-fn compile_and_test(exercise: &Exercise, run_mode: RunMode, verbose: bool, success_hints: bool) -> Result<bool, ()> {
+// the output if verbose is set to true. Failed tests are summarized
+// with their panic messages unless verbose is set.
+fn compile_and_test(
+    exercise: &Exercise,
+    run_mode: RunMode,
+    verbose: bool,
+    success_hints: bool,
+    filter: Option<&str>,
+) -> Result<bool, RustlingsError> {
     let progress_bar = ProgressBar::new_spinner();
     progress_bar.set_message(format!("Testing {exercise}..."));
     progress_bar.enable_steady_tick(100);
 
     let compilation = compile(exercise, &progress_bar)?;
-    let result = compilation.run();
+    let result = match filter {
+        Some(filter) => compilation.run_tests(filter),
+        None => compilation.run(),
+    };
     progress_bar.finish_and_clear();
 
     match result {
         Ok(output) => {
+            if let Some(filter) = filter {
+                // Some of the tests passing doesn't mean the exercise does
+                let results = libtest::parse(&output.stdout);
+                if results.is_empty() {
+                    return Err(RustlingsError::NoMatchingTests {
+                        exercise: exercise.name.clone(),
+                        filter: filter.to_string(),
+                    });
+                }
+                if !verbose {
+                    print!("{}", libtest::report(&results).unwrap_or_default());
+                }
+            } else {
+                cache::store(exercise, &output);
+            }
             if verbose {
                 println!("{}", output.stdout);
             }
+            // The output of a perf exercise tells how long its benchmark took
+            let timing = (exercise.mode == Mode::Perf && filter.is_none()).then_some(output.stdout);
             if let RunMode::Interactive = run_mode {
-                Ok(prompt_for_completion(exercise, None, success_hints))
+                Ok(prompt_for_completion(exercise, timing, success_hints))
             } else {
+                if let Some(timing) = timing.filter(|_| !verbose) {
+                    print!("{timing}");
+                }
                 Ok(true)
             }
         }
-        Err(output) => {
-            warn!(
-                "Testing of {} failed! Please try again. Here's the output:",
-                exercise
-            );
-            println!("{}", output.stdout);
-            Err(())
+        Err(err) => {
+            if let Some(output) = err.output() {
+                if let RustlingsError::TooSlow { .. } = err {
+                    warn!(
+                        "{} is too slow! Please try again. Here's how long it took:",
+                        exercise
+                    );
+                    print!("{}", output.stdout);
+                } else {
+                    warn!(
+                        "Testing of {} failed! Please try again. Here's the output:",
+                        exercise
+                    );
+                    match libtest::report(&libtest::parse(&output.stdout)) {
+                        Some(report) if !verbose => print!("{report}"),
+                        _ => println!("{}", output.stdout),
+                    }
+                }
+                warn_limit_exceeded(exercise, output);
+            }
+            Err(err)
         }
     }
 }
 
 // Compile the given Exercise and return an object with information
 // about the state of the compilation
-fn compile<'a, 'b>(
+pub fn compile<'a>(
     exercise: &'a Exercise,
-    progress_bar: &'b ProgressBar,
-) -> Result<CompiledExercise<'a>, ()> {
+    progress_bar: &ProgressBar,
+) -> Result<CompiledExercise<'a>, RustlingsError> {
     let compilation_result = exercise.compile();
 
-    match compilation_result {
-        Ok(compilation) => Ok(compilation),
-        Err(output) => {
-            progress_bar.finish_and_clear();
-            warn!(
-                "Compiling of {} failed! Please try again. Here's the output:",
-                exercise
-            );
+    if let Err(err) = &compilation_result {
+        progress_bar.finish_and_clear();
+        if let Some(output) = err.output() {
+            let codes = exercise.expected_errors();
+            match err {
+                RustlingsError::CompiledUnexpectedly { .. } => warn!(
+                    "{} compiles, but the compiler has to reject it with {codes}",
+                    exercise
+                ),
+                RustlingsError::Unformatted { exercise: name, .. } => {
+                    warn!(
+                        "{} isn't formatted the way rustfmt formats it, `rustlings fmt {name}` fixes that:",
+                        exercise
+                    );
+                    print!("{}", output.stdout);
+                }
+                _ => warn!(
+                    "Compiling of {} failed! Please try again. Here's the output:",
+                    exercise
+                ),
+            }
             println!("{}", output.stderr);
-            Err(())
+            if exercise.mode == Mode::CompileFail && !output.diagnostics.is_empty() {
+                warn!("The compiler has to reject {} with {codes} alone", exercise);
+            }
+            warn_limit_exceeded(exercise, output);
+        }
+    }
+    compilation_result
+}
+
+// Explain why the exercise was killed, if it hit one of its limits
+pub fn warn_limit_exceeded(exercise: &Exercise, output: &ExerciseOutput) {
+    if let Some(limit) = output.limit_exceeded {
+        warn!("Stopped {}: {limit}", exercise);
+    }
+}
+
+// Explain how the run of the exercise differs from what is expected of it
+pub fn warn_mismatch(exercise: &Exercise, output: &ExerciseOutput) {
+    if let Some(mismatch) = exercise.mismatch(output) {
+        warn!("{} {mismatch}", exercise);
+        if let Some(diff) = mismatch.diff() {
+            print!("{diff}");
         }
     }
 }
@@ -155,6 +325,10 @@ fn prompt_for_completion(exercise: &Exercise, prompt_output: Option<String>, suc
         Mode::Test => success!("Successfully tested {}!", exercise),
         Mode::Clippy => success!("Successfully compiled {}!", exercise),
         Mode::BuildScript => success!("Successfully compiled {}!", exercise),
+        Mode::Doctest => success!("Successfully tested {}!", exercise),
+        Mode::CompileFail => success!("{} is rejected as expected!", exercise),
+        Mode::Fmt => success!("Successfully compiled {}!", exercise),
+        Mode::Perf => success!("Successfully tested {}!", exercise),
     }
 
     let no_emoji = env::var("NO_EMOJI").is_ok();
@@ -170,6 +344,10 @@ fn prompt_for_completion(exercise: &Exercise, prompt_output: Option<String>, suc
         Mode::Test => "The code is compiling, and the tests pass!",
         Mode::Clippy => clippy_success_msg,
         Mode::BuildScript => "Build script works!",
+        Mode::Doctest => "The code is compiling, and the examples in its documentation pass!",
+        Mode::CompileFail => "The compiler rejects the code with the expected error!",
+        Mode::Fmt => "The code is compiling, and formatted the way rustfmt formats it!",
+        Mode::Perf => "The code is compiling, the tests pass, and it is fast enough!",
     };
     println!();
     if no_emoji {
@@ -0,0 +1,121 @@
+use glob::{Pattern, PatternError};
+use notify::{DebouncedEvent, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use std::env;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+use std::time::Duration;
+
+// Files that are never exercises, however they change
+const DEFAULT_IGNORE: &[&str] = &["**/tempCodeRunnerFile*"];
+// How long changes are collected before they are reported,
+// and how often files are checked when polling
+const DELAY: Duration = Duration::from_secs(1);
+
+// How the exercises are watched for changes
+pub struct WatchOptions {
+    // Check the files for changes regularly instead of relying on
+    // notifications, which some file systems never send
+    pub poll: bool,
+    // Files matching these are ignored, on top of the default ones
+    pub ignore: Vec<Pattern>,
+}
+
+impl WatchOptions {
+    pub fn new(poll: bool, ignore: &[String]) -> Result<WatchOptions, PatternError> {
+        let ignore = DEFAULT_IGNORE
+            .iter()
+            .copied()
+            .chain(ignore.iter().map(String::as_str))
+            .map(Pattern::new)
+            .collect::<Result<_, _>>()?;
+        Ok(WatchOptions { poll, ignore })
+    }
+
+    fn is_ignored(&self, path: &Path) -> bool {
+        let relative = env::current_dir()
+            .ok()
+            .and_then(|dir| path.strip_prefix(dir).ok().map(Path::to_path_buf));
+        self.ignore.iter().any(|pattern| {
+            pattern.matches_path(path) || relative.as_ref().is_some_and(|p| pattern.matches_path(p))
+        })
+    }
+}
+
+// Watch the exercises directory, sending the canonical path of every
+// Rust file that was written, created, or renamed into place.
+// Editors that save by renaming a temporary file over the exercise, or by
+// removing and recreating it, end up here just like ones writing in place.
+pub fn changed_files(options: WatchOptions) -> notify::Result<Receiver<PathBuf>> {
+    let (event_tx, event_rx) = channel();
+    // The watcher stops watching once dropped, so it lives in the thread below
+    let watcher: Box<dyn Send> = if options.poll {
+        let mut watcher = PollWatcher::new(event_tx, DELAY)?;
+        watcher.watch(Path::new("./exercises"), RecursiveMode::Recursive)?;
+        Box::new(watcher)
+    } else {
+        let mut watcher: RecommendedWatcher = Watcher::new(event_tx, DELAY)?;
+        watcher.watch(Path::new("./exercises"), RecursiveMode::Recursive)?;
+        Box::new(watcher)
+    };
+
+    let (tx, rx) = channel();
+    thread::spawn(move || {
+        let _watcher = watcher;
+        for event in event_rx {
+            let Some(path) = changed_path(event, &options) else {
+                continue;
+            };
+            if tx.send(path).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(rx)
+}
+
+// The canonical path of the Rust file an event leaves changed, if any
+fn changed_path(event: DebouncedEvent, options: &WatchOptions) -> Option<PathBuf> {
+    let path = match event {
+        DebouncedEvent::Create(path)
+        | DebouncedEvent::Write(path)
+        | DebouncedEvent::Chmod(path)
+        | DebouncedEvent::Rename(_, path) => path,
+        _ => return None,
+    };
+    if path.extension() != Some(OsStr::new("rs")) || !path.exists() || options.is_ignored(&path) {
+        return None;
+    }
+    Some(path.canonicalize().unwrap_or(path))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ignore_globs() {
+        let options = WatchOptions::new(false, &["exercises/scratch/*.rs".to_string()]).unwrap();
+        assert!(options.is_ignored(Path::new("/home/x/exercises/intro/tempCodeRunnerFile.rs")));
+        assert!(options.is_ignored(Path::new("exercises/scratch/notes.rs")));
+        assert!(!options.is_ignored(Path::new("exercises/intro/intro1.rs")));
+    }
+
+    #[test]
+    fn test_atomic_saves_are_changes() {
+        let options = WatchOptions::new(false, &[]).unwrap();
+        let exercise = Path::new("tests/fixture/reset/edited.rs");
+        let canonical = exercise.canonicalize().unwrap();
+        let renamed = DebouncedEvent::Rename(
+            PathBuf::from("tests/fixture/reset/.edited.rs.swp"),
+            exercise.to_path_buf(),
+        );
+        assert_eq!(changed_path(renamed, &options), Some(canonical.clone()));
+
+        let removed = DebouncedEvent::Remove(exercise.to_path_buf());
+        assert_eq!(changed_path(removed, &options), None);
+        let created = DebouncedEvent::Create(exercise.to_path_buf());
+        assert_eq!(changed_path(created, &options), Some(canonical));
+    }
+}
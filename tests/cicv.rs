@@ -5,8 +5,26 @@ use std::process::Command;
 fn cicvverify() {
     Command::cargo_bin("rustlings")
         .unwrap()
-        .args(&["--nocapture", "cicvverify"]) 
+        .args(["--nocapture", "cicvverify"]) 
         // .current_dir("exercises")
         .assert()
         .success();
 }
+
+#[test]
+fn cicvverify_writes_junit_report() {
+    let report = std::env::temp_dir()
+        .join(format!("rustlings_report_{}", std::process::id()))
+        .join("report.xml");
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--format", "junit", "--output"])
+        .arg(&report)
+        .current_dir("tests/fixture/failure")
+        .assert()
+        .success();
+    let xml = std::fs::read_to_string(&report).unwrap();
+    assert!(xml.contains(r#"<testcase name="compFailure" classname="rustlings.compile""#));
+    assert!(xml.find("compFailure") < xml.find("testFailure"));
+    assert_eq!(xml.matches("<failure").count(), 2);
+}
@@ -0,0 +1,4 @@
+fn main() {
+    let s: String = 1;
+    println!("{s}");
+}
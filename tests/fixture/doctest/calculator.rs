@@ -0,0 +1,17 @@
+/// Adds two numbers
+///
+/// ```
+/// assert_eq!(calculator::add(1, 2), 3);
+/// ```
+pub fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+/// Subtracts the second number from the first
+///
+/// ```
+/// assert_eq!(calculator::sub(3, 1), 3);
+/// ```
+pub fn sub(a: i32, b: i32) -> i32 {
+    a - b
+}
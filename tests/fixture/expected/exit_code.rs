@@ -0,0 +1,4 @@
+fn main() {
+    println!("3 errors");
+    std::process::exit(3);
+}
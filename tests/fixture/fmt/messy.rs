@@ -0,0 +1,5 @@
+fn main()
+{
+  let greeting=  "Hello";
+  println!("{greeting}");
+}
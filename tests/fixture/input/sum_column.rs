@@ -0,0 +1,13 @@
+use std::io::{self, BufRead};
+
+fn main() {
+    let column = std::env::args().nth(1).unwrap();
+    let separator = std::env::var("SEPARATOR").unwrap();
+    let mut lines = io::stdin().lock().lines().map(Result::unwrap);
+    let header = lines.next().unwrap();
+    let index = header.split(&separator).position(|c| c == column).unwrap();
+    let sum: u32 = lines
+        .map(|line| line.split(&separator).nth(index).unwrap().parse::<u32>().unwrap())
+        .sum();
+    println!("{column}: {sum}");
+}
@@ -0,0 +1,30 @@
+fn sort<T: Ord>(array: &mut [T]) {
+    let len = array.len();
+    for i in 0..len {
+        for j in 0..len - 1 - i {
+            if array[j] > array[j + 1] {
+                array.swap(j, j + 1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts() {
+        let mut vec = vec![37, 73, 57, 75, 91, 19];
+        sort(&mut vec);
+        assert_eq!(vec, vec![19, 37, 57, 73, 75, 91]);
+    }
+
+    #[test]
+    #[ignore = "benchmark"]
+    fn sorts_many() {
+        let mut vec: Vec<u32> = (0..100_000).rev().collect();
+        sort(&mut vec);
+        assert!(vec.windows(2).all(|w| w[0] <= w[1]));
+    }
+}
@@ -0,0 +1,42 @@
+fn sort<T: Ord + Clone>(array: &mut [T]) {
+    if array.len() < 2 {
+        return;
+    }
+    let mid = array.len() / 2;
+    sort(&mut array[..mid]);
+    sort(&mut array[mid..]);
+    let mut merged = Vec::with_capacity(array.len());
+    let (mut i, mut j) = (0, mid);
+    while i < mid && j < array.len() {
+        if array[j] < array[i] {
+            merged.push(array[j].clone());
+            j += 1;
+        } else {
+            merged.push(array[i].clone());
+            i += 1;
+        }
+    }
+    merged.extend_from_slice(&array[i..mid]);
+    merged.extend_from_slice(&array[j..]);
+    array.clone_from_slice(&merged);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts() {
+        let mut vec = vec![37, 73, 57, 75, 91, 19];
+        sort(&mut vec);
+        assert_eq!(vec, vec![19, 37, 57, 73, 75, 91]);
+    }
+
+    #[test]
+    #[ignore = "benchmark"]
+    fn sorts_many() {
+        let mut vec: Vec<u32> = (0..50_000).rev().collect();
+        sort(&mut vec);
+        assert!(vec.windows(2).all(|w| w[0] <= w[1]));
+    }
+}
@@ -0,0 +1,4 @@
+fn main() {
+    let greeting = "Hello, world!";
+    println!("{greeting}");
+}
@@ -0,0 +1,17 @@
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn passes() {
+        assert_eq!(1 + 1, 2);
+    }
+
+    #[test]
+    fn fails() {
+        println!("printed before failing");
+        assert_eq!(vec![1, 2, 3], vec![1, 5, 3]);
+    }
+
+    #[test]
+    #[ignore]
+    fn ignored() {}
+}
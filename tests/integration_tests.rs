@@ -1,9 +1,69 @@
 use assert_cmd::prelude::*;
 use glob::glob;
 use predicates::boolean::PredicateBooleanExt;
-use std::fs::File;
-use std::io::Read;
-use std::process::Command;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+// A copy of a fixture in the temp directory for tests that change it,
+// removed again once dropped. The state rustlings keeps in `.rustlings`
+// isn't copied, so every copy starts from scratch.
+struct TempFixture {
+    path: PathBuf,
+}
+
+impl TempFixture {
+    fn new(name: &str) -> TempFixture {
+        static COPIES: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "rustlings-{name}-{}-{}",
+            std::process::id(),
+            COPIES.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = fs::remove_dir_all(&path);
+        copy_dir(&Path::new("tests/fixture").join(name), &path);
+        TempFixture { path }
+    }
+}
+
+impl Drop for TempFixture {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+// A copy of the state fixture in which finished_exercise passed `verify`
+fn state_fixture() -> TempFixture {
+    let fixture = TempFixture::new("state");
+    let state = fixture.path.join(".rustlings");
+    fs::create_dir_all(&state).unwrap();
+    fs::write(
+        state.join("progress.json"),
+        r#"{"exercises": {"finished_exercise": {"hash": "47f996173ef834b9", "verified_at": 1700000000, "mode": "compile"}}}"#,
+    )
+    .unwrap();
+    fixture
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        if entry.file_name() == ".rustlings" {
+            continue;
+        }
+        let target = to.join(entry.file_name());
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &target);
+        } else {
+            fs::copy(entry.path(), target).unwrap();
+        }
+    }
+}
 
 #[test]
 fn runs_without_arguments() {
@@ -17,7 +77,7 @@ fn fails_when_in_wrong_dir() {
         .unwrap()
         .current_dir("tests/")
         .assert()
-        .code(1);
+        .code(23);
 }
 
 #[test]
@@ -37,14 +97,14 @@ fn verify_fails_if_some_fails() {
         .arg("verify")
         .current_dir("tests/fixture/failure")
         .assert()
-        .code(1);
+        .code(2);
 }
 
 #[test]
 fn run_single_compile_success() {
     Command::cargo_bin("rustlings")
         .unwrap()
-        .args(&["run", "compSuccess"])
+        .args(["run", "compSuccess"])
         .current_dir("tests/fixture/success/")
         .assert()
         .success();
@@ -54,17 +114,31 @@ fn run_single_compile_success() {
 fn run_single_compile_failure() {
     Command::cargo_bin("rustlings")
         .unwrap()
-        .args(&["run", "compFailure"])
+        .args(["run", "compFailure"])
         .current_dir("tests/fixture/failure/")
         .assert()
-        .code(1);
+        .code(2);
+}
+
+#[test]
+fn run_compile_failure_renders_diagnostics() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "compFailure"])
+        .current_dir("tests/fixture/failure/")
+        .assert()
+        .code(2)
+        .stdout(predicates::str::contains(
+            "error: expected pattern, found `}`\n  --> compFailure.rs:3:1\n",
+        ))
+        .stdout(predicates::str::contains("\n1 error\n"));
 }
 
 #[test]
 fn run_single_test_success() {
     Command::cargo_bin("rustlings")
         .unwrap()
-        .args(&["run", "testSuccess"])
+        .args(["run", "testSuccess"])
         .current_dir("tests/fixture/success/")
         .assert()
         .success();
@@ -74,20 +148,20 @@ fn run_single_test_success() {
 fn run_single_test_failure() {
     Command::cargo_bin("rustlings")
         .unwrap()
-        .args(&["run", "testFailure"])
+        .args(["run", "testFailure"])
         .current_dir("tests/fixture/failure/")
         .assert()
-        .code(1);
+        .code(2);
 }
 
 #[test]
 fn run_single_test_not_passed() {
     Command::cargo_bin("rustlings")
         .unwrap()
-        .args(&["run", "testNotPassed.rs"])
+        .args(["run", "testNotPassed.rs"])
         .current_dir("tests/fixture/failure/")
         .assert()
-        .code(1);
+        .code(23);
 }
 
 #[test]
@@ -97,24 +171,24 @@ fn run_single_test_no_filename() {
         .arg("run")
         .current_dir("tests/fixture/")
         .assert()
-        .code(1);
+        .code(23);
 }
 
 #[test]
 fn run_single_test_no_exercise() {
     Command::cargo_bin("rustlings")
         .unwrap()
-        .args(&["run", "compNoExercise.rs"])
+        .args(["run", "compNoExercise.rs"])
         .current_dir("tests/fixture/failure")
         .assert()
-        .code(1);
+        .code(23);
 }
 
 #[test]
 fn reset_single_exercise() {
     Command::cargo_bin("rustlings")
         .unwrap()
-        .args(&["reset", "intro1"])
+        .args(["reset", "intro1"])
         .assert()
         .code(0);
 }
@@ -125,17 +199,50 @@ fn reset_no_exercise() {
         .unwrap()
         .arg("reset")
         .assert()
-        .code(1)
+        .code(23)
         .stderr(predicates::str::contains(
-            "positional arguments not provided",
+            "Pass either the name of an exercise",
         ));
 }
 
+#[test]
+fn reset_restores_the_original() {
+    let fixture = TempFixture::new("reset");
+    let path = fixture.path.join("edited.rs");
+    let original = std::fs::read_to_string(&path).unwrap();
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["diff", "edited"])
+        .current_dir(&fixture.path)
+        .assert()
+        .success()
+        .stdout("You haven't changed edited yet.\n");
+
+    std::fs::write(&path, original.replace("Hello", "Bye")).unwrap();
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["diff", "edited"])
+        .current_dir(&fixture.path)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("-    println!(\"Hello\");"))
+        .stdout(predicates::str::contains("+    println!(\"Bye\");"));
+
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["reset", "--all"])
+        .current_dir(&fixture.path)
+        .assert()
+        .success()
+        .stdout("Reset edited\nReset 1 of 2 exercises.\n");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+}
+
 #[test]
 fn get_hint_for_single_test() {
     Command::cargo_bin("rustlings")
         .unwrap()
-        .args(&["hint", "testFailure"])
+        .args(["hint", "testFailure"])
         .current_dir("tests/fixture/failure")
         .assert()
         .code(0)
@@ -171,7 +278,7 @@ fn all_exercises_require_confirmation() {
 fn run_compile_exercise_does_not_prompt() {
     Command::cargo_bin("rustlings")
         .unwrap()
-        .args(&["run", "pending_exercise"])
+        .args(["run", "pending_exercise"])
         .current_dir("tests/fixture/state")
         .assert()
         .code(0)
@@ -182,7 +289,7 @@ fn run_compile_exercise_does_not_prompt() {
 fn run_test_exercise_does_not_prompt() {
     Command::cargo_bin("rustlings")
         .unwrap()
-        .args(&["run", "pending_test_exercise"])
+        .args(["run", "pending_test_exercise"])
         .current_dir("tests/fixture/state")
         .assert()
         .code(0)
@@ -193,7 +300,7 @@ fn run_test_exercise_does_not_prompt() {
 fn run_single_test_success_with_output() {
     Command::cargo_bin("rustlings")
         .unwrap()
-        .args(&["--nocapture", "run", "testSuccess"])
+        .args(["--nocapture", "run", "testSuccess"])
         .current_dir("tests/fixture/success/")
         .assert()
         .code(0)
@@ -204,7 +311,7 @@ fn run_single_test_success_with_output() {
 fn run_single_test_success_without_output() {
     Command::cargo_bin("rustlings")
         .unwrap()
-        .args(&["run", "testSuccess"])
+        .args(["run", "testSuccess"])
         .current_dir("tests/fixture/success/")
         .assert()
         .code(0)
@@ -215,7 +322,7 @@ fn run_single_test_success_without_output() {
 fn run_rustlings_list() {
     Command::cargo_bin("rustlings")
         .unwrap()
-        .args(&["list"])
+        .args(["list"])
         .current_dir("tests/fixture/success")
         .assert()
         .success();
@@ -225,7 +332,13 @@ fn run_rustlings_list() {
 fn run_rustlings_list_no_pending() {
     Command::cargo_bin("rustlings")
         .unwrap()
-        .args(&["list"])
+        .arg("verify")
+        .current_dir("tests/fixture/success")
+        .assert()
+        .success();
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["list"])
         .current_dir("tests/fixture/success")
         .assert()
         .success()
@@ -234,10 +347,11 @@ fn run_rustlings_list_no_pending() {
 
 #[test]
 fn run_rustlings_list_both_done_and_pending() {
+    let fixture = state_fixture();
     Command::cargo_bin("rustlings")
         .unwrap()
-        .args(&["list"])
-        .current_dir("tests/fixture/state")
+        .args(["list"])
+        .current_dir(&fixture.path)
         .assert()
         .success()
         .stdout(predicates::str::contains("Done").and(predicates::str::contains("Pending")));
@@ -245,10 +359,11 @@ fn run_rustlings_list_both_done_and_pending() {
 
 #[test]
 fn run_rustlings_list_without_pending() {
+    let fixture = state_fixture();
     Command::cargo_bin("rustlings")
         .unwrap()
-        .args(&["list", "--solved"])
-        .current_dir("tests/fixture/state")
+        .args(["list", "--solved"])
+        .current_dir(&fixture.path)
         .assert()
         .success()
         .stdout(predicates::str::contains("Pending").not());
@@ -256,11 +371,489 @@ fn run_rustlings_list_without_pending() {
 
 #[test]
 fn run_rustlings_list_without_done() {
+    let fixture = state_fixture();
     Command::cargo_bin("rustlings")
         .unwrap()
-        .args(&["list", "--unsolved"])
-        .current_dir("tests/fixture/state")
+        .args(["list", "--unsolved"])
+        .current_dir(&fixture.path)
         .assert()
         .success()
         .stdout(predicates::str::contains("Done").not());
 }
+
+#[test]
+fn run_rustlings_list_solved_shows_verified() {
+    let fixture = state_fixture();
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["list", "--solved"])
+        .current_dir(&fixture.path)
+        .assert()
+        .success()
+        .stdout(
+            predicates::str::contains("finished_exercise")
+                .and(predicates::str::contains("pending_exercise").not()),
+        );
+}
+
+#[test]
+fn run_single_exercise_times_out() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "infiniteLoop"])
+        .current_dir("tests/fixture/limits")
+        .assert()
+        .code(5)
+        .stdout(predicates::str::contains("timed out after 1.0s"));
+}
+
+#[test]
+fn invalid_manifest_is_an_environment_error() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .arg("verify")
+        .current_dir("tests/fixture/invalid_manifest")
+        .assert()
+        .code(22)
+        .stderr(predicates::str::contains("Invalid info.toml"));
+}
+
+#[test]
+fn check_manifest_reports_locations() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .arg("check-manifest")
+        .current_dir("tests/fixture/check_manifest")
+        .assert()
+        .code(22)
+        .stdout(predicates::str::contains(
+            "info.toml:8:8: duplicate exercise name `pending`",
+        ))
+        .stdout(predicates::str::contains(
+            "info.toml:9:8: `exercises/missing.rs` doesn't exist",
+        ))
+        .stdout(predicates::str::contains("unknown variant `benchmark`"))
+        .stdout(predicates::str::contains(
+            "info.toml:24:12: prerequisite `late` has to come before `early`",
+        ))
+        .stdout(predicates::str::contains("unknown prerequisite `nope`"))
+        .stdout(predicates::str::contains(
+            "exercises/unlisted.rs: not listed",
+        ));
+}
+
+#[test]
+fn check_manifest_passes_valid_manifest() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .arg("check-manifest")
+        .current_dir("tests/fixture/success")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("0 error(s)"));
+}
+
+#[test]
+fn startup_rejects_manifest_errors() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .arg("list")
+        .current_dir("tests/fixture/check_manifest")
+        .assert()
+        .code(22)
+        .stderr(predicates::str::contains("duplicate exercise name"));
+}
+
+#[test]
+fn list_includes_other_manifests() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["list", "--names"])
+        .current_dir("tests/fixture/tracks")
+        .assert()
+        .success()
+        .stdout("compSuccess\nextraSuccess\nProgress: You completed 0 / 2 exercises (0.0 %).\n");
+}
+
+#[test]
+fn list_single_track() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["list", "--names", "--track", "extra"])
+        .current_dir("tests/fixture/tracks")
+        .assert()
+        .success()
+        .stdout("extraSuccess\nProgress: You completed 0 / 1 exercises (0.0 %).\n");
+}
+
+#[test]
+fn verify_unknown_track() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["verify", "--track", "missing"])
+        .current_dir("tests/fixture/tracks")
+        .assert()
+        .code(23)
+        .stderr(predicates::str::contains("Available tracks: main, extra"));
+}
+
+#[test]
+fn list_shows_locked_exercises() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .arg("list")
+        .current_dir("tests/fixture/prerequisites")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("first            \tpending.rs"))
+        .stdout(predicates::str::contains("Pending"))
+        .stdout(predicates::str::contains("Locked"));
+}
+
+#[test]
+fn hint_reveals_levels() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["hint", "pending"])
+        .current_dir("tests/fixture/hints")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Hint 1 of 2:\nFirst hint\n"));
+}
+
+#[test]
+fn solution_is_hidden_until_verified() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["solution", "unsolved"])
+        .current_dir("tests/fixture/solutions")
+        .assert()
+        .code(23)
+        .stderr(predicates::str::contains("only shown once you solved it"));
+}
+
+#[test]
+fn solution_diff_after_verifying() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .arg("verify")
+        .current_dir("tests/fixture/solutions")
+        .assert()
+        .code(2);
+
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["solution", "solved", "--diff"])
+        .current_dir("tests/fixture/solutions")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "-    println!(\"Hello, world!\");\n+    let greeting = \"Hello, world!\";",
+        ));
+}
+
+#[test]
+fn verify_solutions_pass() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["verify", "--solutions"])
+        .current_dir("tests/fixture/solutions")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Checked 2 solutions"));
+}
+
+#[test]
+fn verify_solutions_fails_on_broken_solution() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["verify", "--solutions"])
+        .current_dir("tests/fixture/failure")
+        .assert()
+        .code(2)
+        .stdout(predicates::str::contains(
+            "The solution of testFailure fails",
+        ));
+}
+
+#[test]
+fn verify_without_cache() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["--no-cache", "verify"])
+        .current_dir("tests/fixture/success")
+        .assert()
+        .success();
+}
+
+#[test]
+fn cache_clear() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cache", "clear"])
+        .current_dir("tests/fixture/state")
+        .assert()
+        .success()
+        .stdout("The cache is empty now.\n");
+}
+
+#[test]
+fn restore_needs_a_saved_version() {
+    let fixture = TempFixture::new("reset");
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["history", "untouched"])
+        .current_dir(&fixture.path)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "There is no history of untouched yet",
+        ));
+
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["restore", "untouched", "1"])
+        .current_dir(&fixture.path)
+        .assert()
+        .code(23)
+        .stderr("error: There is no version 1 of untouched, see `rustlings history untouched`.\n");
+}
+
+#[test]
+fn run_reports_failed_tests() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "failing_tests"])
+        .current_dir("tests/fixture/tests")
+        .assert()
+        .code(3)
+        .stdout(predicates::str::contains(
+            "  FAILED   tests::fails\n  ignored  tests::ignored\n  ok       tests::passes\n",
+        ))
+        .stdout(predicates::str::contains(
+            "  left: [1, 2, 3]\n right: [1, 5, 3]\n",
+        ))
+        .stdout(predicates::str::contains("printed before failing").not());
+}
+
+#[test]
+fn run_single_test() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "failing_tests", "--test", "passes"])
+        .current_dir("tests/fixture/tests")
+        .assert()
+        .success()
+        .stdout("  ok       tests::passes\n1 passed, 0 failed, 0 ignored\n");
+}
+
+#[test]
+fn run_single_test_without_a_match() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "failing_tests", "--test", "missing"])
+        .current_dir("tests/fixture/tests")
+        .assert()
+        .code(23)
+        .stderr("error: no test of failing_tests matches `missing`\n");
+}
+
+#[test]
+fn skipped_exercises_come_last() {
+    let fixture = TempFixture::new("skip");
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["skip", "stuck"])
+        .current_dir(&fixture.path)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Skipped stuck"));
+
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["list"])
+        .current_dir(&fixture.path)
+        .assert()
+        .success()
+        .stdout(predicates::str::is_match("stuck +\tstuck.rs +\tSkipped").unwrap());
+
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["hint", "next"])
+        .current_dir(&fixture.path)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Hint of later"));
+
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["skip", "--undo", "stuck"])
+        .current_dir(&fixture.path)
+        .assert()
+        .success()
+        .stdout("stuck is back in its place.\n");
+}
+
+#[test]
+fn run_compares_the_expected_output() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "greeting"])
+        .current_dir("tests/fixture/expected")
+        .assert()
+        .code(7)
+        .stdout(
+            predicates::str::contains("didn't print the expected output")
+                .and(predicates::str::contains("-Hello, world!"))
+                .and(predicates::str::contains("+Hello world!")),
+        );
+
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "exit_code"])
+        .current_dir("tests/fixture/expected")
+        .assert()
+        .success();
+}
+
+#[test]
+fn run_passes_stdin_args_and_env() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "sum_column"])
+        .current_dir("tests/fixture/input")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("amount: 7"));
+}
+
+#[test]
+fn compile_fail_needs_the_error_codes() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "use_after_move"])
+        .current_dir("tests/fixture/compile_fail")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("error[E0382]"));
+
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "no_move"])
+        .current_dir("tests/fixture/compile_fail")
+        .assert()
+        .code(10)
+        .stdout(predicates::str::contains(
+            "compiles, but the compiler has to reject it with E0382",
+        ));
+
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "mismatched_types"])
+        .current_dir("tests/fixture/compile_fail")
+        .assert()
+        .code(2)
+        .stdout(predicates::str::contains("error[E0308]"));
+}
+
+#[test]
+fn run_reports_failed_doctests() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "calculator"])
+        .current_dir("tests/fixture/doctest")
+        .assert()
+        .code(3)
+        .stdout(
+            predicates::str::contains("calculator.rs - sub (line 12)")
+                .and(predicates::str::contains("1 passed, 1 failed, 0 ignored")),
+        );
+
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "calculator", "--test", "add"])
+        .current_dir("tests/fixture/doctest")
+        .assert()
+        .success();
+}
+
+#[test]
+fn fmt_shows_and_applies_the_changes() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "unformatted"])
+        .current_dir("tests/fixture/fmt")
+        .assert()
+        .code(8)
+        .stdout(
+            predicates::str::contains("`rustlings fmt unformatted` fixes that")
+                .and(predicates::str::contains("+fn main() {")),
+        );
+
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "tidy_tests"])
+        .current_dir("tests/fixture/fmt")
+        .assert()
+        .success();
+
+    let fixture = TempFixture::new("fmt");
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["fmt", "messy"])
+        .current_dir(&fixture.path)
+        .assert()
+        .success()
+        .stdout("Formatted messy\n");
+    let formatted = std::fs::read_to_string(fixture.path.join("messy.rs")).unwrap();
+    assert!(formatted.contains("    let greeting = \"Hello\";"));
+}
+
+#[test]
+fn perf_exercises_have_a_time_budget() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "merge_sort"])
+        .current_dir("tests/fixture/perf")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("the budget is 5s"));
+
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "bubble_sort"])
+        .current_dir("tests/fixture/perf")
+        .assert()
+        .code(9)
+        .stdout(predicates::str::contains("bubble_sort.rs is too slow"));
+}
+
+#[test]
+fn watch_with_focus_records_no_history_for_other_exercises() {
+    let fixture = TempFixture::new("watch");
+    let mut watch = Command::cargo_bin("rustlings")
+        .unwrap()
+        .arg("watch")
+        .current_dir(&fixture.path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut stdin = watch.stdin.take().unwrap();
+    stdin.write_all(b"focus focused\n").unwrap();
+    thread::sleep(Duration::from_secs(2));
+
+    // Only the focused exercise is verified, whichever one is edited
+    for name in ["edited", "focused"] {
+        let path = fixture.path.join(format!("exercises/{name}.rs"));
+        let source = fs::read_to_string(&path).unwrap();
+        fs::write(&path, source + "// edited\n").unwrap();
+        thread::sleep(Duration::from_secs(4));
+    }
+    stdin.write_all(b"quit\n").unwrap();
+    watch.wait().unwrap();
+
+    let history = fixture.path.join(".rustlings/history");
+    assert!(!history.join("edited.json").exists());
+    assert!(history.join("focused.json").exists());
+}